@@ -1,31 +1,54 @@
-use crate::gitlab::models::PipelineDetails;
-use crate::gitlab_config::{GitLabConfig, GitLabConfigDialog, RemoteInfo};
+use crate::bisect::Bisect;
+use crate::gitlab::analytics::JobAnalytics;
+use crate::gitlab::ci_config::{preview_pipeline, PreviewRef, CI_CONFIG_PATH};
+use crate::gitlab::models::{
+    LintResult, Pipeline, PipelineDetails, PipelinePolicy, PipelineStatus, PipelineVariable,
+};
+use crate::gitlab::problems::{default_matchers, find_problems};
+use crate::gitlab_config::{repository_key, GitLabConfig, GitLabConfigDialog, RemoteInfo};
 use crate::settings::AppSettings;
 use crate::theme;
 use crate::util::syntax_highlight::highlight;
-use crate::widgets::branches_view::{BranchItem, BranchItemType};
+use crate::util::{browser, clipboard, notify};
+use crate::widgets::analytics_view::AnalyticsViewState;
+use crate::widgets::branches_view::{
+    status_rank, BranchFilter, BranchItem, BranchItemType, BranchSort,
+};
 use crate::widgets::commit_view::{CommitViewInfo, CommitViewState, DiffItem};
+use crate::widgets::compare_view::CompareViewState;
 use crate::widgets::diff_view::{DiffViewInfo, DiffViewState};
-use crate::widgets::graph_view::GraphViewState;
+use crate::widgets::graph_view::{GraphFilter, GraphViewState, StatusGroup};
+use crate::widgets::history_view::HistoryViewState;
+use crate::widgets::lint_view::LintViewState;
 use crate::widgets::list::StatefulList;
 use crate::widgets::models_view::ModelListState;
 use crate::widgets::pipeline_view::{CachedPipeline, PipelineViewState};
-use git2::{Commit, DiffDelta, DiffFormat, DiffHunk, DiffLine, DiffOptions as GDiffOptions, Oid};
+use crate::widgets::problems_view::ProblemsViewState;
+use crate::widgets::remotes_view::RemoteListState;
+use git2::{
+    Commit, DiffDelta, DiffFormat, DiffHunk, DiffLine, DiffOptions as GDiffOptions, Oid, Tree,
+};
 use git_graph::config::get_available_models;
-use git_graph::graph::GitGraph;
+use git_graph::graph::{BranchInfo, GitGraph};
 use git_graph::print::unicode::{format_branches, print_unicode};
 use git_graph::settings::Settings;
 use ratatui::style::Color;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
-use std::path::PathBuf;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write as _};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::mpsc::Sender;
+use tempfile::TempPath;
 
 pub const DEFAULT_PIPELINE_LOAD_LIMIT: usize = 1000;
 
 const HASH_COLOR: u8 = 11;
 
+/// Lines shown above a problem's line when opening it in the diff panel.
+const PROBLEM_CONTEXT_LINES: usize = 5;
+
 #[derive(PartialEq, Eq)]
 pub enum ActiveView {
     Branches,
@@ -38,6 +61,12 @@ pub enum ActiveView {
     Help(u16),
     Pipeline,
     GitLabConfig,
+    Remotes,
+    History,
+    Analytics,
+    Problems,
+    Lint,
+    Compare,
     Logo,
 }
 
@@ -118,6 +147,15 @@ pub type DiffLines = Vec<(String, Option<u32>, Option<u32>)>;
 
 pub struct PipelineRequest {
     pub sha: String,
+    /// Pipeline chosen by the user; the policy selects one if `None` or not found.
+    pub pipeline_id: Option<u64>,
+    pub policy: PipelinePolicy,
+    /// Also fetch attempts of jobs that were retried.
+    pub include_retried: bool,
+    /// Also fetch trigger jobs, which only the pipeline panel shows.
+    pub with_bridges: bool,
+    /// Pipelines of merge requests with the commit as source branch tip.
+    pub merge_request_pipelines: Vec<u64>,
     pub base_url: String,
     pub project_id: String,
     pub token: String,
@@ -128,6 +166,20 @@ pub struct PipelineResponse {
     pub result: Result<Option<PipelineDetails>, String>,
 }
 
+pub struct PipelineInfoRequest {
+    pub pipeline_id: u64,
+    pub with_variables: bool,
+    pub base_url: String,
+    pub project_id: String,
+    pub token: String,
+}
+
+pub struct PipelineInfoResponse {
+    pub pipeline_id: u64,
+    pub result: Result<Pipeline, String>,
+    pub variables: Option<Result<Vec<PipelineVariable>, String>>,
+}
+
 pub struct JobLogRequest {
     pub job_id: u64,
     pub job_name: String,
@@ -136,6 +188,73 @@ pub struct JobLogRequest {
     pub token: String,
 }
 
+pub struct HistoryRequest {
+    pub ref_name: String,
+    pub base_url: String,
+    pub project_id: String,
+    pub token: String,
+}
+
+pub struct HistoryResponse {
+    pub ref_name: String,
+    pub result: Result<Vec<Pipeline>, String>,
+}
+
+pub struct AnalyticsRequest {
+    pub ref_name: String,
+    pub base_url: String,
+    pub project_id: String,
+    pub token: String,
+}
+
+pub struct AnalyticsResponse {
+    pub ref_name: String,
+    pub result: Result<JobAnalytics, String>,
+}
+
+pub struct LintRequest {
+    pub sha: String,
+    pub content: String,
+    pub ref_name: Option<String>,
+    pub base_url: String,
+    pub project_id: String,
+    pub token: String,
+}
+
+pub struct LintResponse {
+    pub sha: String,
+    pub result: Result<LintResult, String>,
+}
+
+pub struct CompareRequest {
+    pub base_sha: String,
+    pub head_sha: String,
+    pub policy: PipelinePolicy,
+    pub base_merge_request_pipelines: Vec<u64>,
+    pub head_merge_request_pipelines: Vec<u64>,
+    pub base_url: String,
+    pub project_id: String,
+    pub token: String,
+}
+
+pub struct CompareResponse {
+    pub base_sha: String,
+    pub head_sha: String,
+    pub result: Result<(Option<PipelineDetails>, Option<PipelineDetails>), String>,
+}
+
+pub struct MergeRequestPipelinesRequest {
+    /// Source branches to find open merge requests for, with their tips.
+    pub branches: HashMap<String, HashSet<String>>,
+    pub base_url: String,
+    pub project_id: String,
+    pub token: String,
+}
+
+pub struct MergeRequestPipelinesResponse {
+    pub result: Result<HashMap<String, Vec<Pipeline>>, String>,
+}
+
 pub struct JobLogResponse {
     pub job_id: u64,
     pub job_name: String,
@@ -149,12 +268,24 @@ pub struct App {
     pub diff_state: DiffViewState,
     pub pipeline_state: PipelineViewState,
     pub models_state: Option<ModelListState>,
+    pub remotes_state: Option<RemoteListState>,
+    pub history_state: Option<HistoryViewState>,
+    pub analytics_state: Option<AnalyticsViewState>,
+    pub problems_state: Option<ProblemsViewState>,
+    pub lint_state: Option<LintViewState>,
+    pub compare_state: Option<CompareViewState>,
     pub gitlab_config: GitLabConfig,
     pub gitlab_config_dialog: Option<GitLabConfigDialog>,
     pub remote_info: Option<RemoteInfo>,
     pub pipeline_tx: Option<Sender<PipelineRequest>>,
     pub head_pipeline_tx: Option<Sender<PipelineRequest>>,
+    pub pipeline_info_tx: Option<Sender<PipelineInfoRequest>>,
     pub job_log_tx: Option<Sender<JobLogRequest>>,
+    pub history_tx: Option<Sender<HistoryRequest>>,
+    pub analytics_tx: Option<Sender<AnalyticsRequest>>,
+    pub lint_tx: Option<Sender<LintRequest>>,
+    pub compare_tx: Option<Sender<CompareRequest>>,
+    pub merge_request_tx: Option<Sender<MergeRequestPipelinesRequest>>,
     pub title: String,
     pub repo_name: String,
     pub active_view: ActiveView,
@@ -170,7 +301,16 @@ pub struct App {
     pub diff_options: DiffOptions,
     pub search_term: Option<String>,
     pub pipeline_load_limit: usize,
+    /// Selects the pipeline shown for commits with several and their status in the graph.
+    pub pipeline_policy: PipelinePolicy,
     pending_pipeline_requests: HashSet<String>,
+    /// IDs of merge request pipelines by the source branch tip they belong to.
+    merge_request_pipelines: HashMap<String, Vec<u64>>,
+    merge_requests_pending: bool,
+    /// Branches whose pipelines trigger a notification when they finish (besides HEAD).
+    pub watched_branches: HashSet<String>,
+    pub branch_sort: BranchSort,
+    pub branch_filter: Option<BranchFilter>,
     pub animation_tick: u8,
 }
 
@@ -189,12 +329,24 @@ impl App {
             diff_state: DiffViewState::default(),
             pipeline_state: PipelineViewState::default(),
             models_state: None,
+            remotes_state: None,
+            history_state: None,
+            analytics_state: None,
+            problems_state: None,
+            lint_state: None,
+            compare_state: None,
             gitlab_config,
             gitlab_config_dialog: None,
             remote_info: None,
             pipeline_tx: None,
             head_pipeline_tx: None,
             job_log_tx: None,
+            history_tx: None,
+            analytics_tx: None,
+            lint_tx: None,
+            pipeline_info_tx: None,
+            compare_tx: None,
+            merge_request_tx: None,
             title,
             repo_name,
             active_view: ActiveView::Graph,
@@ -210,7 +362,13 @@ impl App {
             diff_options: DiffOptions::default(),
             search_term: None,
             pipeline_load_limit: DEFAULT_PIPELINE_LOAD_LIMIT,
+            pipeline_policy: PipelinePolicy::default(),
             pending_pipeline_requests: HashSet::new(),
+            merge_request_pipelines: HashMap::new(),
+            merge_requests_pending: false,
+            watched_branches: HashSet::new(),
+            branch_sort: BranchSort::default(),
+            branch_filter: None,
             animation_tick: 0,
         }
     }
@@ -249,6 +407,30 @@ impl App {
         self.job_log_tx = Some(tx);
     }
 
+    pub fn set_history_channel(&mut self, tx: Sender<HistoryRequest>) {
+        self.history_tx = Some(tx);
+    }
+
+    pub fn set_analytics_channel(&mut self, tx: Sender<AnalyticsRequest>) {
+        self.analytics_tx = Some(tx);
+    }
+
+    pub fn set_pipeline_info_channel(&mut self, tx: Sender<PipelineInfoRequest>) {
+        self.pipeline_info_tx = Some(tx);
+    }
+
+    pub fn set_lint_channel(&mut self, tx: Sender<LintRequest>) {
+        self.lint_tx = Some(tx);
+    }
+
+    pub fn set_compare_channel(&mut self, tx: Sender<CompareRequest>) {
+        self.compare_tx = Some(tx);
+    }
+
+    pub fn set_merge_request_channel(&mut self, tx: Sender<MergeRequestPipelinesRequest>) {
+        self.merge_request_tx = Some(tx);
+    }
+
     pub fn request_job_log(&mut self) {
         let (base_url, project_id, host) = match &self.remote_info {
             Some(r) if r.is_valid() => (
@@ -264,9 +446,19 @@ impl App {
             None => return,
         };
 
-        let job_id = match self.pipeline_state.get_selected_job_id() {
-            Some(id) => id,
-            None => {
+        if self
+            .pipeline_state
+            .details
+            .as_ref()
+            .is_some_and(|d| d.preview)
+        {
+            self.pipeline_state.clear_job_log();
+            return;
+        }
+
+        let job_id = match self.pipeline_state.selected_job() {
+            Some(job) if !job.bridge => job.id,
+            _ => {
                 self.pipeline_state.clear_job_log();
                 return;
             }
@@ -336,6 +528,264 @@ impl App {
         }
     }
 
+    fn selected_job_name(&self) -> Option<String> {
+        self.pipeline_state.selected_job().map(|j| j.name.clone())
+    }
+
+    /// Copies the primary (or secondary) target of the active panel to the clipboard:
+    /// commit hash (message), file path, diff hunk (whole diff), job log or job URL
+    /// (pipeline URL), branch name.
+    pub fn copy_to_clipboard(&mut self, secondary: bool) -> Result<(), String> {
+        let text = match self.active_view {
+            ActiveView::Graph | ActiveView::Commit => {
+                let graph = match &self.graph_state.graph {
+                    Some(graph) => graph,
+                    None => return Ok(()),
+                };
+                let oid = match self
+                    .graph_state
+                    .selected
+                    .and_then(|idx| graph.commits.get(idx))
+                {
+                    Some(info) => info.oid,
+                    None => return Ok(()),
+                };
+                if secondary {
+                    let commit = graph
+                        .repository
+                        .find_commit(oid)
+                        .map_err(|err| err.message().to_string())?;
+                    commit.message().map(|m| m.to_string())
+                } else {
+                    Some(oid.to_string())
+                }
+            }
+            ActiveView::Branches => self.graph_state.branches.as_ref().and_then(|state| {
+                let item = &state.items[state.state.selected()?];
+                item.index.map(|_| item.name.clone())
+            }),
+            ActiveView::Files => self.commit_state.content.as_ref().and_then(|content| {
+                let idx = content.diffs.state.selected()?;
+                Some(content.diffs.items[idx].file.clone())
+            }),
+            ActiveView::Diff => self.diff_state.content.as_ref().map(|content| {
+                if secondary {
+                    content
+                        .diffs
+                        .iter()
+                        .map(|(line, _, _)| line.as_str())
+                        .collect()
+                } else {
+                    diff_hunk_at(&content.diffs, content.scroll.0 as usize)
+                }
+            }),
+            ActiveView::Pipeline => {
+                if secondary {
+                    self.pipeline_state
+                        .details
+                        .as_ref()
+                        .and_then(|d| d.pipeline.as_ref())
+                        .and_then(|p| p.web_url.clone())
+                } else if self.pipeline_state.job_log_focused
+                    && !self.pipeline_state.job_log.is_empty()
+                {
+                    Some(self.pipeline_state.job_log_as_text())
+                } else {
+                    self.pipeline_state
+                        .selected_job()
+                        .and_then(|j| j.web_url.clone())
+                }
+            }
+            _ => None,
+        };
+
+        match text {
+            Some(text) if !text.is_empty() => clipboard::copy(&text),
+            _ => Ok(()),
+        }
+    }
+
+    /// Opens the web page of the active panel's selection in the browser, or copies its URL:
+    /// the commit, the file at the commit (and line in the diff panel), the merge requests
+    /// of a branch, or the job (pipeline if no job is selected).
+    pub fn open_web_url(&mut self, copy: bool) -> Result<(), String> {
+        let remote = match &self.remote_info {
+            Some(r) if r.is_valid() => r,
+            _ => return Err("No GitLab remote found".to_string()),
+        };
+        let page = match self.web_page() {
+            Some(page) => page,
+            None => return Ok(()),
+        };
+        let url = match remote.web_url(&page) {
+            Some(url) => url,
+            None => return Ok(()),
+        };
+        if copy {
+            clipboard::copy(&url)
+        } else {
+            browser::open(&url)
+        }
+    }
+
+    /// Project-relative web page of the active panel's selection, see [`App::open_web_url`].
+    fn web_page(&self) -> Option<String> {
+        let graph = self.graph_state.graph.as_ref()?;
+        let selected_oid = self
+            .graph_state
+            .selected
+            .and_then(|idx| graph.commits.get(idx))
+            .map(|info| info.oid);
+        let selected_file = || {
+            let content = self.commit_state.content.as_ref()?;
+            let idx = content.diffs.state.selected()?;
+            Some(content.diffs.items[idx].file.clone())
+        };
+        let blob = |oid: Oid, file: &str, line: Option<u32>| {
+            let path: Vec<String> = file.split('/').map(escape_path_segment).collect();
+            let anchor = line.map(|l| format!("#L{}", l)).unwrap_or_default();
+            format!("-/blob/{}/{}{}", oid, path.join("/"), anchor)
+        };
+
+        match self.active_view {
+            ActiveView::Graph | ActiveView::Commit => {
+                selected_oid.map(|oid| format!("-/commit/{}", oid))
+            }
+            ActiveView::Files => Some(blob(selected_oid?, &selected_file()?, None)),
+            ActiveView::Diff => {
+                let content = self.diff_state.content.as_ref()?;
+                let file = selected_file()?;
+                let (oid, line) = match &self.diff_options.diff_mode {
+                    DiffMode::Diff => content
+                        .diffs
+                        .iter()
+                        .skip(content.scroll.0 as usize)
+                        .find_map(|(_, old, new)| {
+                            new.map(|n| (content.oid, n))
+                                .or_else(|| old.map(|o| (content.compare_oid, o)))
+                        })
+                        .map(|(oid, line)| (oid, Some(line)))
+                        .unwrap_or((content.oid, None)),
+                    mode => {
+                        let oid = if *mode == DiffMode::Old {
+                            content.compare_oid
+                        } else {
+                            content.oid
+                        };
+                        let top = (content.scroll.0 as u32).max(1);
+                        (oid, Some(content.marked_line.unwrap_or(top)))
+                    }
+                };
+                if oid.is_zero() {
+                    return None;
+                }
+                Some(blob(oid, &file, line))
+            }
+            ActiveView::Branches => {
+                let state = self.graph_state.branches.as_ref()?;
+                let item = &state.items[state.state.selected()?];
+                let branch = &graph.all_branches[item.index?];
+                if branch.is_tag {
+                    Some(format!("-/tags/{}", escape_path_segment(&branch.name)))
+                } else {
+                    // Remote branches are named `<remote>/<branch>`
+                    let name = if branch.is_remote {
+                        branch
+                            .name
+                            .split_once('/')
+                            .map_or(&*branch.name, |(_, n)| n)
+                    } else {
+                        &branch.name
+                    };
+                    Some(format!(
+                        "-/merge_requests?scope=all&state=all&source_branch={}",
+                        url::form_urlencoded::byte_serialize(name.as_bytes()).collect::<String>()
+                    ))
+                }
+            }
+            ActiveView::Pipeline => {
+                let details = self
+                    .pipeline_state
+                    .details
+                    .as_ref()
+                    .filter(|d| !d.preview)?;
+                match self.pipeline_state.selected_job() {
+                    Some(job) => Some(format!("-/jobs/{}", job.id)),
+                    None => Some(format!("-/pipelines/{}", details.pipeline.as_ref()?.id)),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// File name for an exported job log, e.g. `job-1234-unit-tests.log`.
+    fn job_log_file_name(&self) -> Option<String> {
+        let job_id = self.pipeline_state.job_log_job_id?;
+        let name: String = self
+            .selected_job_name()
+            .unwrap_or_default()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect();
+        Some(format!("job-{}-{}.log", job_id, name.trim_matches('-')))
+    }
+
+    /// Writes the raw or ANSI-stripped job log to the current directory. Existing files
+    /// are kept; a number is appended to the name instead, e.g. `job-1234-test-2.log`.
+    pub fn export_job_log(&mut self, raw: bool) -> Result<(), String> {
+        const MAX_SUFFIX: usize = 1000;
+
+        let file_name = match self.job_log_file_name() {
+            Some(name) if !self.pipeline_state.job_log.is_empty() => name,
+            _ => return Ok(()),
+        };
+        let dir = std::env::current_dir().map_err(|err| err.to_string())?;
+        let stem = file_name.trim_end_matches(".log");
+        let mut suffix = 0;
+        let (path, mut file) = loop {
+            let path = match suffix {
+                0 => dir.join(&file_name),
+                n => dir.join(format!("{}-{}.log", stem, n)),
+            };
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => break (path, file),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists && suffix < MAX_SUFFIX => {
+                    suffix += 1
+                }
+                Err(err) => return Err(format!("Failed to write {}: {}", path.display(), err)),
+            }
+        };
+        let text = if raw {
+            self.pipeline_state.job_log_raw.clone()
+        } else {
+            self.pipeline_state.job_log_export_text(false)
+        };
+        file.write_all(text.as_bytes())
+            .map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+        self.pipeline_state.job_log_notice = Some(format!("saved to {}", path.display()));
+        Ok(())
+    }
+
+    /// Writes the job log to a new temporary file with a random name for viewing in a
+    /// pager or editor. The file is deleted when the returned path is dropped.
+    pub fn job_log_temp_file(&self, ansi: bool) -> Result<Option<TempPath>, String> {
+        let file_name = match self.job_log_file_name() {
+            Some(name) if !self.pipeline_state.job_log.is_empty() => name,
+            _ => return Ok(None),
+        };
+        let mut file = tempfile::Builder::new()
+            .prefix(&format!(
+                "git-igitt-{}-",
+                file_name.trim_end_matches(".log")
+            ))
+            .suffix(".log")
+            .tempfile()
+            .map_err(|err| format!("Failed to create a temporary file: {}", err))?;
+        file.write_all(self.pipeline_state.job_log_export_text(ansi).as_bytes())
+            .map_err(|err| format!("Failed to write {}: {}", file.path().display(), err))?;
+        Ok(Some(file.into_temp_path()))
+    }
+
     pub fn with_graph(
         mut self,
         graph: GitGraph,
@@ -345,14 +795,33 @@ impl App {
         select_head: bool,
     ) -> Result<App, String> {
         let branches = get_branches(&graph);
-        self.remote_info = Some(RemoteInfo::from_repository(&graph.repository));
+        self.remote_info = Some(RemoteInfo::from_repository(
+            &graph.repository,
+            &self.gitlab_config,
+        ));
 
+        self.graph_state.bisect = match Bisect::load(&graph.repository) {
+            Ok(bisect) => bisect,
+            Err(err) => {
+                self.set_error(format!("Can't load bisect state: {}", err));
+                None
+            }
+        };
+        self.watched_branches = self
+            .gitlab_config
+            .get_watched(&repository_key(&graph.repository))
+            .iter()
+            .cloned()
+            .collect();
         self.graph_state.graph = Some(graph);
 
         self.graph_state.graph_lines = graph_lines;
         self.graph_state.text_lines = text_lines;
         self.graph_state.indices = indices;
         self.graph_state.branches = Some(StatefulList::with_items(branches));
+        if self.branch_sort != BranchSort::Name || self.branch_filter.is_some() {
+            self.refresh_branch_list();
+        }
 
         if select_head {
             if let Some(graph) = &self.graph_state.graph {
@@ -398,6 +867,7 @@ impl App {
                 .map(|info| info.oid);
             let repo = graph.take_repository();
             let graph = GitGraph::new(repo, settings, max_commits)?;
+            self.pipeline_state.clear_previews();
             let (graph_lines, text_lines, indices) = print_unicode(&graph, settings)?;
 
             let sel_idx = sel_oid.and_then(|oid| graph.indices.get(&oid)).cloned();
@@ -459,6 +929,36 @@ impl App {
                     state.bwd(step)
                 }
             }
+            ActiveView::Remotes => {
+                if let Some(state) = &mut self.remotes_state {
+                    state.bwd(step)
+                }
+            }
+            ActiveView::History => {
+                if let Some(state) = &mut self.history_state {
+                    state.bwd(step)
+                }
+            }
+            ActiveView::Analytics => {
+                if let Some(state) = &mut self.analytics_state {
+                    state.bwd(step)
+                }
+            }
+            ActiveView::Problems => {
+                if let Some(state) = &mut self.problems_state {
+                    state.bwd(step)
+                }
+            }
+            ActiveView::Lint => {
+                if let Some(state) = &mut self.lint_state {
+                    state.bwd(step)
+                }
+            }
+            ActiveView::Compare => {
+                if let Some(state) = &mut self.compare_state {
+                    state.bwd(step)
+                }
+            }
             ActiveView::Pipeline => {
                 if is_shift || self.pipeline_state.job_log_focused {
                     self.pipeline_state.job_log_scroll = self
@@ -521,11 +1021,44 @@ impl App {
                     state.fwd(step)
                 }
             }
+            ActiveView::Remotes => {
+                if let Some(state) = &mut self.remotes_state {
+                    state.fwd(step)
+                }
+            }
+            ActiveView::History => {
+                if let Some(state) = &mut self.history_state {
+                    state.fwd(step)
+                }
+            }
+            ActiveView::Analytics => {
+                if let Some(state) = &mut self.analytics_state {
+                    state.fwd(step)
+                }
+            }
+            ActiveView::Problems => {
+                if let Some(state) = &mut self.problems_state {
+                    state.fwd(step)
+                }
+            }
+            ActiveView::Lint => {
+                if let Some(state) = &mut self.lint_state {
+                    state.fwd(step)
+                }
+            }
+            ActiveView::Compare => {
+                if let Some(state) = &mut self.compare_state {
+                    state.fwd(step)
+                }
+            }
             ActiveView::Pipeline => {
                 if is_shift || self.pipeline_state.job_log_focused {
                     let visible = self.pipeline_state.job_log_visible_height as usize;
-                    let max_scroll =
-                        self.pipeline_state.job_log.len().saturating_sub(visible) as u16;
+                    let max_scroll = self
+                        .pipeline_state
+                        .job_log_visible
+                        .len()
+                        .saturating_sub(visible) as u16;
                     self.pipeline_state.job_log_scroll =
                         (self.pipeline_state.job_log_scroll + step as u16).min(max_scroll);
                 } else {
@@ -553,6 +1086,81 @@ impl App {
         Ok(false)
     }
 
+    /// Selects the next older (or newer) commit whose pipeline is in the group.
+    pub fn select_commit_by_status(&mut self, group: StatusGroup, older: bool) -> bool {
+        match self.graph_state.find_by_status(group, older) {
+            Some(idx) => {
+                self.graph_state.selected = Some(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Starts a bisection, like `git bisect start`.
+    pub fn bisect_start(&mut self) -> Result<(), String> {
+        let graph = match &self.graph_state.graph {
+            Some(graph) => graph,
+            None => return Ok(()),
+        };
+        if self.graph_state.bisect.is_some() {
+            return Err("A bisection is already in progress. End it with Shift+G".to_string());
+        }
+        self.graph_state.bisect = Some(Bisect::start(&graph.repository)?);
+        Ok(())
+    }
+
+    /// Marks the selected commit good or bad. Commits with a known pipeline result are
+    /// marked as well, then the next commit to test (or the first bad one) is selected.
+    pub fn bisect_mark(&mut self, good: bool) -> Result<bool, String> {
+        let graph = match &self.graph_state.graph {
+            Some(graph) => graph,
+            None => return Ok(false),
+        };
+        let oid = match self
+            .graph_state
+            .selected
+            .and_then(|idx| graph.commits.get(idx))
+        {
+            Some(commit) => commit.oid,
+            None => return Ok(false),
+        };
+        let repo = &graph.repository;
+        let mut bisect = match self.graph_state.bisect.take() {
+            Some(bisect) => bisect,
+            None => {
+                return Err("No bisection in progress. Start one with Ctrl+G".to_string());
+            }
+        };
+        let result = bisect
+            .mark(repo, oid, good)
+            .and_then(|_| bisect.auto_mark(repo, &self.graph_state.pipeline_statuses));
+        let target = bisect.next.or(bisect.first_bad());
+        self.graph_state.bisect = Some(bisect);
+        result?;
+
+        match target.and_then(|oid| graph.indices.get(&oid)) {
+            Some(idx) if self.graph_state.selected != Some(*idx) => {
+                self.graph_state.selected = Some(*idx);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Ends the bisection and removes its state from the repository.
+    pub fn bisect_reset(&mut self) -> Result<(), String> {
+        if let (Some(graph), Some(_)) = (&self.graph_state.graph, self.graph_state.bisect.take()) {
+            Bisect::reset(&graph.repository)?;
+        }
+        Ok(())
+    }
+
+    /// Cycles the graph's status filter: failed, failed or running, running, passed, off.
+    pub fn cycle_graph_filter(&mut self) {
+        self.graph_state.status_filter = GraphFilter::next(self.graph_state.status_filter);
+    }
+
     pub fn on_end(&mut self) -> Result<bool, String> {
         if let ActiveView::Graph = self.active_view {
             if !self.graph_state.indices.is_empty() {
@@ -624,6 +1232,12 @@ impl App {
                     ActiveView::Pipeline
                 }
                 ActiveView::GitLabConfig => ActiveView::GitLabConfig,
+                ActiveView::Remotes => ActiveView::Remotes,
+                ActiveView::History => ActiveView::History,
+                ActiveView::Analytics => ActiveView::Analytics,
+                ActiveView::Problems => ActiveView::Problems,
+                ActiveView::Lint => ActiveView::Lint,
+                ActiveView::Compare => ActiveView::Compare,
                 ActiveView::Logo => ActiveView::Logo,
             }
         }
@@ -675,6 +1289,12 @@ impl App {
                     ActiveView::Pipeline
                 }
                 ActiveView::GitLabConfig => ActiveView::GitLabConfig,
+                ActiveView::Remotes => ActiveView::Remotes,
+                ActiveView::History => ActiveView::History,
+                ActiveView::Analytics => ActiveView::Analytics,
+                ActiveView::Problems => ActiveView::Problems,
+                ActiveView::Lint => ActiveView::Lint,
+                ActiveView::Compare => ActiveView::Compare,
                 ActiveView::Logo => ActiveView::Logo,
             }
         }
@@ -726,7 +1346,14 @@ impl App {
 
     pub fn on_backspace(&mut self) -> Result<bool, String> {
         match &self.active_view {
-            ActiveView::Help(_) | ActiveView::Models => {}
+            ActiveView::Help(_)
+            | ActiveView::Models
+            | ActiveView::Remotes
+            | ActiveView::History
+            | ActiveView::Analytics
+            | ActiveView::Problems
+            | ActiveView::Lint
+            | ActiveView::Compare => {}
             ActiveView::Search => {
                 if let Some(term) = &self.search_term {
                     let term = &term[0..(term.len() - 1)];
@@ -770,8 +1397,21 @@ impl App {
 
     pub fn on_esc(&mut self) -> Result<bool, String> {
         match self.active_view {
-            ActiveView::Models | ActiveView::Help(_) | ActiveView::Logo => {
+            ActiveView::Models
+            | ActiveView::Remotes
+            | ActiveView::History
+            | ActiveView::Analytics
+            | ActiveView::Problems
+            | ActiveView::Lint
+            | ActiveView::Compare
+            | ActiveView::Help(_)
+            | ActiveView::Logo => {
                 self.active_view = self.prev_active_view.take().unwrap_or(ActiveView::Graph);
+                self.history_state = None;
+                self.analytics_state = None;
+                self.problems_state = None;
+                self.lint_state = None;
+                self.compare_state = None;
             }
             ActiveView::Search => {
                 self.active_view = self.prev_active_view.take().unwrap_or(ActiveView::Graph);
@@ -836,13 +1476,13 @@ impl App {
                 0
             };
             for idx in search_start..self.graph_state.indices.len() {
-                if self.commit_contains(idx, &term) {
+                if !self.graph_state.is_dimmed(idx) && self.commit_contains(idx, &term) {
                     self.graph_state.selected = Some(idx);
                     return Ok(true);
                 }
             }
             for idx in 0..search_start {
-                if self.commit_contains(idx, &term) {
+                if !self.graph_state.is_dimmed(idx) && self.commit_contains(idx, &term) {
                     self.graph_state.selected = Some(idx);
                     return Ok(true);
                 }
@@ -1002,13 +1642,26 @@ impl App {
 
         if let Some(cached) = self.pipeline_state.get_cached(&sha).cloned() {
             self.pipeline_state.apply_cached(&sha, &cached);
-            return;
+            if matches!(cached, CachedPipeline::NotFound) {
+                self.show_ci_preview(&sha);
+            } else {
+                self.request_pipeline_info();
+            }
+            // Pipelines fetched for the graph are shown until the trigger jobs are loaded
+            if !matches!(&cached, CachedPipeline::Found(details) if !details.with_bridges) {
+                return;
+            }
+        } else {
+            self.pipeline_state.set_loading(Some(sha.clone()));
         }
 
-        self.pipeline_state.set_loading(Some(sha.clone()));
-
         if let Some(tx) = &self.pipeline_tx {
             let _ = tx.send(PipelineRequest {
+                pipeline_id: self.pipeline_state.pinned_pipeline(&sha),
+                policy: self.pipeline_policy.clone(),
+                include_retried: self.pipeline_state.include_retried,
+                with_bridges: true,
+                merge_request_pipelines: self.merge_request_pipelines_of(&sha),
                 sha,
                 base_url,
                 project_id,
@@ -1020,36 +1673,64 @@ impl App {
     pub fn handle_pipeline_response(&mut self, response: PipelineResponse) {
         let is_current = self.pipeline_state.current_sha.as_ref() == Some(&response.sha);
         self.pending_pipeline_requests.remove(&response.sha);
+        let prev_status = self
+            .graph_state
+            .pipeline_statuses
+            .get(&response.sha)
+            .copied();
 
         match response.result {
             Ok(details) => {
                 let cached = match &details {
-                    Some(d) => CachedPipeline::Found(d.clone()),
+                    Some(d) => CachedPipeline::Found(Box::new(d.clone())),
                     None => CachedPipeline::NotFound,
                 };
                 self.pipeline_state
                     .cache_result(response.sha.clone(), cached);
 
                 if let Some(ref d) = details {
-                    if let Some(ref pipeline) = d.pipeline {
+                    if let Some(status) = d.status_by(&self.pipeline_policy) {
                         self.graph_state
                             .pipeline_statuses
-                            .insert(response.sha.clone(), pipeline.status);
+                            .insert(response.sha.clone(), status);
+                        if prev_status != Some(status)
+                            && (self.branch_sort != BranchSort::Name
+                                || self.branch_filter.is_some())
+                        {
+                            self.refresh_branch_list();
+                        }
+                        if prev_status.is_some_and(|s| s.is_active()) && status.is_finished() {
+                            self.notify_pipeline_finished(&response.sha, status);
+                        }
                     }
                 }
 
                 if is_current {
                     let was_loaded = self.pipeline_state.details.is_some();
                     let was_running = self.pipeline_state.is_running();
+                    let not_found = details.is_none();
+                    let with_bridges = details.as_ref().is_some_and(|d| d.with_bridges);
                     self.pipeline_state
-                        .set_pipeline(Some(response.sha), details);
+                        .set_pipeline(Some(response.sha.clone()), details);
                     let now_running = self.pipeline_state.is_running();
                     let selected_is_running = self.pipeline_state.selected_job_is_running();
-                    if !was_loaded
-                        || (now_running && !selected_is_running)
-                        || (was_running && !now_running)
-                    {
-                        self.pipeline_state.auto_scroll_to_active();
+                    if not_found {
+                        self.show_ci_preview(&response.sha);
+                    } else {
+                        if !was_loaded
+                            || (now_running && !selected_is_running)
+                            || (was_running && !now_running)
+                        {
+                            self.pipeline_state.auto_scroll_to_active();
+                        }
+                        if !with_bridges
+                            && self.get_selected_commit_sha().as_ref() == Some(&response.sha)
+                        {
+                            // Fetched for the graph before the commit was selected
+                            self.request_pipeline();
+                        } else {
+                            self.request_pipeline_info();
+                        }
                     }
                 }
             }
@@ -1064,13 +1745,13 @@ impl App {
         }
     }
 
-    pub fn invalidate_current_pipeline(&mut self) {
-        if let Some(sha) = &self.pipeline_state.current_sha.clone() {
-            self.pipeline_state.invalidate_cache(sha);
-        }
-    }
-
-    pub fn recheck_head_pipeline(&mut self) {
+    /// Fetches the full details of the shown pipeline for the pipeline header,
+    /// and its variables when they are shown.
+    fn request_pipeline_info(&mut self) {
+        let (pipeline_id, with_variables) = match self.pipeline_state.needs_info() {
+            Some(needed) => needed,
+            None => return,
+        };
         let (base_url, project_id, host) = match &self.remote_info {
             Some(r) if r.is_valid() => (
                 r.url.as_ref().unwrap().clone(),
@@ -1079,32 +1760,15 @@ impl App {
             ),
             _ => return,
         };
-
         let token = match self.gitlab_config.get_token(&host) {
             Some(t) => t.to_string(),
             None => return,
         };
-
-        let graph = match &self.graph_state.graph {
-            Some(g) => g,
-            None => return,
-        };
-
-        let head_sha = match graph.commits.first() {
-            Some(c) => c.oid.to_string(),
-            None => return,
-        };
-
-        if self.pending_pipeline_requests.contains(&head_sha) {
-            return;
-        }
-
-        self.pipeline_state.invalidate_cache(&head_sha);
-        self.pending_pipeline_requests.insert(head_sha.clone());
-
-        if let Some(tx) = &self.head_pipeline_tx {
-            let _ = tx.send(PipelineRequest {
-                sha: head_sha,
+        if let Some(tx) = &self.pipeline_info_tx {
+            self.pipeline_state.set_info_pending(pipeline_id);
+            let _ = tx.send(PipelineInfoRequest {
+                pipeline_id,
+                with_variables,
                 base_url,
                 project_id,
                 token,
@@ -1112,7 +1776,307 @@ impl App {
         }
     }
 
-    pub fn request_batch_pipelines(&mut self) {
+    pub fn handle_pipeline_info_response(&mut self, response: PipelineInfoResponse) {
+        self.pipeline_state
+            .set_info(response.pipeline_id, response.result, response.variables);
+    }
+
+    /// Shows the next older (or newer) pipeline of the commit shown in the pipeline panel.
+    pub fn switch_pipeline(&mut self, older: bool) {
+        let (sha, id) = match (
+            &self.pipeline_state.current_sha,
+            &self.pipeline_state.details,
+        ) {
+            (Some(sha), Some(details)) if details.pipelines.len() > 1 => {
+                let current = details.pipeline.as_ref().map(|p| p.id);
+                let idx = details
+                    .pipelines
+                    .iter()
+                    .position(|p| Some(p.id) == current)
+                    .unwrap_or(0);
+                let new_idx = if older {
+                    (idx + 1).min(details.pipelines.len() - 1)
+                } else {
+                    idx.saturating_sub(1)
+                };
+                if new_idx == idx {
+                    return;
+                }
+                (sha.clone(), details.pipelines[new_idx].id)
+            }
+            _ => return,
+        };
+        self.pipeline_state.pin_pipeline(sha.clone(), id);
+        self.pipeline_state.invalidate_cache(&sha);
+        self.request_pipeline();
+    }
+
+    pub fn toggle_include_retried(&mut self) {
+        self.pipeline_state.toggle_include_retried();
+        self.request_pipeline();
+    }
+
+    pub fn toggle_pipeline_variables(&mut self) {
+        self.pipeline_state.show_variables = !self.pipeline_state.show_variables;
+        self.request_pipeline_info();
+    }
+
+    /// Shows the pipeline the commit's `.gitlab-ci.yml` would create, for commits without one.
+    /// Previews are cached by commit, as the configuration is parsed on the UI thread.
+    fn show_ci_preview(&mut self, sha: &str) {
+        let cached = match self.pipeline_state.get_cached_preview(sha) {
+            Some(cached) => cached.clone(),
+            None => {
+                let preview = self.preview_ci_config(sha);
+                self.pipeline_state
+                    .cache_preview(sha.to_string(), preview.clone());
+                preview
+            }
+        };
+        match cached {
+            CachedPipeline::Found(details) => {
+                self.pipeline_state.set_preview(sha.to_string(), *details)
+            }
+            CachedPipeline::NotFound => {}
+            CachedPipeline::Error(err) => {
+                self.pipeline_state.error = Some(format!("Invalid CI configuration: {}", err))
+            }
+        }
+    }
+
+    fn preview_ci_config(&self, sha: &str) -> CachedPipeline {
+        let graph = match &self.graph_state.graph {
+            Some(graph) => graph,
+            None => return CachedPipeline::NotFound,
+        };
+        let oid = match Oid::from_str(sha) {
+            Ok(oid) => oid,
+            Err(_) => return CachedPipeline::NotFound,
+        };
+        let remote = self
+            .remote_info
+            .as_ref()
+            .and_then(|r| r.name.as_deref())
+            .unwrap_or("origin");
+        let target = PreviewRef {
+            branch: branch_at_commit(graph, oid),
+            default_branch: default_branch(graph, remote),
+        };
+        match preview_pipeline(&graph.repository, oid, &target) {
+            Ok(Some(details)) => CachedPipeline::Found(Box::new(details)),
+            Ok(None) => CachedPipeline::NotFound,
+            Err(err) => CachedPipeline::Error(err),
+        }
+    }
+
+    pub fn invalidate_current_pipeline(&mut self) {
+        if let Some(sha) = &self.pipeline_state.current_sha.clone() {
+            self.pipeline_state.invalidate_cache(sha);
+        }
+    }
+
+    /// Returns the (name, sha) of HEAD and of all watched branches present in the graph.
+    fn watched_tips(&self) -> Vec<(String, String)> {
+        let graph = match &self.graph_state.graph {
+            Some(g) => g,
+            None => return vec![],
+        };
+
+        let mut tips = vec![(graph.head.name.clone(), graph.head.oid.to_string())];
+        for branch in &graph.all_branches {
+            if self.watched_branches.contains(&branch.name) {
+                tips.push((branch.name.clone(), branch.target.to_string()));
+            }
+        }
+        tips
+    }
+
+    fn notify_pipeline_finished(&self, sha: &str, status: PipelineStatus) {
+        let mut names: Vec<_> = self
+            .watched_tips()
+            .into_iter()
+            .filter(|(_, tip)| tip == sha)
+            .map(|(name, _)| name)
+            .collect();
+        if names.is_empty() {
+            return;
+        }
+        names.dedup();
+        notify::pipeline_finished(
+            &self.gitlab_config.notifications,
+            &names.join(", "),
+            &status.to_string(),
+            status == PipelineStatus::Failed,
+        );
+    }
+
+    /// Switches the branch list between sorting by name and by pipeline status.
+    pub fn cycle_branch_sort(&mut self) {
+        self.branch_sort = self.branch_sort.next();
+        self.refresh_branch_list();
+    }
+
+    /// Switches to the next pipeline status filter of the branch list, or back to all branches.
+    pub fn cycle_branch_filter(&mut self) {
+        self.branch_filter = BranchFilter::next(self.branch_filter);
+        self.refresh_branch_list();
+    }
+
+    /// Rebuilds the branch list with the current sort order and status filter,
+    /// keeping the selected entry if it is still listed.
+    pub fn refresh_branch_list(&mut self) {
+        let graph = match &self.graph_state.graph {
+            Some(graph) => graph,
+            None => return,
+        };
+        let selected = self.graph_state.branches.as_ref().and_then(|state| {
+            let item = &state.items[state.state.selected()?];
+            Some((item.name.clone(), item.branch_type.clone()))
+        });
+
+        let statuses = &self.graph_state.pipeline_statuses;
+        let tip_status = |item: &BranchItem| {
+            let branch = &graph.all_branches[item.index?];
+            statuses.get(&branch.target.to_string()).copied()
+        };
+
+        let mut items: Vec<BranchItem> = vec![];
+        let mut section: Vec<BranchItem> = vec![];
+        let arrange = |section: &mut Vec<BranchItem>, items: &mut Vec<BranchItem>| {
+            if self.branch_sort == BranchSort::Status {
+                section.sort_by_key(|item| status_rank(tip_status(item)));
+            }
+            items.append(section);
+        };
+        for item in get_branches(graph) {
+            if item.branch_type == BranchItemType::Heading {
+                arrange(&mut section, &mut items);
+                items.push(item);
+            } else if self
+                .branch_filter
+                .is_none_or(|filter| filter.matches(tip_status(&item)))
+            {
+                section.push(item);
+            }
+        }
+        arrange(&mut section, &mut items);
+
+        let mut list = StatefulList::with_items(items);
+        list.state.select(selected.and_then(|(name, branch_type)| {
+            list.items
+                .iter()
+                .position(|item| item.name == name && item.branch_type == branch_type)
+        }));
+        self.graph_state.branches = Some(list);
+    }
+
+    /// Toggles watching the branch selected in the branch list, and saves the watched
+    /// branches of the repository.
+    pub fn toggle_watch_branch(&mut self) -> Result<(), String> {
+        let (graph, state) = match (&self.graph_state.graph, &self.graph_state.branches) {
+            (Some(graph), Some(state)) => (graph, state),
+            _ => return Ok(()),
+        };
+        let index = match state
+            .state
+            .selected()
+            .and_then(|sel| state.items[sel].index)
+        {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+        let name = graph.all_branches[index].name.clone();
+        let watch = !self.watched_branches.remove(&name);
+        if watch {
+            self.watched_branches.insert(name);
+        }
+        self.gitlab_config.set_watched(
+            &repository_key(&graph.repository),
+            self.watched_branches.iter().cloned().collect(),
+        );
+        self.gitlab_config.save()?;
+        if watch {
+            self.recheck_head_pipeline();
+        }
+        Ok(())
+    }
+
+    /// Re-fetches the pipelines of HEAD and of watched branches that are still running,
+    /// and the merge request pipelines of their branches.
+    pub fn recheck_head_pipeline(&mut self) {
+        let (base_url, project_id, host) = match &self.remote_info {
+            Some(r) if r.is_valid() => (
+                r.url.as_ref().unwrap().clone(),
+                r.project_id.as_ref().unwrap().clone(),
+                r.host.as_ref().unwrap().clone(),
+            ),
+            _ => return,
+        };
+
+        let token = match self.gitlab_config.get_token(&host) {
+            Some(t) => t.to_string(),
+            None => return,
+        };
+
+        let graph = match &self.graph_state.graph {
+            Some(g) => g,
+            None => return,
+        };
+
+        let head_sha = match graph.commits.first() {
+            Some(c) => c.oid.to_string(),
+            None => return,
+        };
+
+        let mut shas = vec![head_sha];
+        for (_, sha) in self.watched_tips() {
+            let is_done = self
+                .graph_state
+                .pipeline_statuses
+                .get(&sha)
+                .is_some_and(|s| !s.is_active());
+            if !is_done && !shas.contains(&sha) {
+                shas.push(sha);
+            }
+        }
+
+        let tx = match &self.head_pipeline_tx {
+            Some(tx) => tx,
+            None => return,
+        };
+
+        for sha in shas {
+            if self.pending_pipeline_requests.contains(&sha) {
+                continue;
+            }
+
+            self.pipeline_state.invalidate_cache(&sha);
+            self.pending_pipeline_requests.insert(sha.clone());
+
+            let _ = tx.send(PipelineRequest {
+                pipeline_id: self.pipeline_state.pinned_pipeline(&sha),
+                policy: self.pipeline_policy.clone(),
+                include_retried: self.pipeline_state.include_retried,
+                with_bridges: self.pipeline_state.current_sha.as_ref() == Some(&sha),
+                merge_request_pipelines: self.merge_request_pipelines_of(&sha),
+                sha,
+                base_url: base_url.clone(),
+                project_id: project_id.clone(),
+                token: token.clone(),
+            });
+        }
+
+        // Finds merge requests opened since the last reload
+        let names: HashSet<String> = self
+            .watched_tips()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        let branches = self.merge_request_branches(Some(&names));
+        self.request_merge_request_pipelines(branches, base_url, project_id, token);
+    }
+
+    pub fn request_batch_pipelines(&mut self) {
         let (base_url, project_id, host) = match &self.remote_info {
             Some(r) if r.is_valid() => (
                 r.url.as_ref().unwrap().clone(),
@@ -1137,10 +2101,33 @@ impl App {
             None => return,
         };
 
+        // Branch and tag tips are always loaded for the badges in the branch list
         let limit = self.pipeline_load_limit.min(graph.commits.len());
-        for commit_info in graph.commits.iter().take(limit) {
-            let sha = commit_info.oid.to_string();
-
+        let mut seen = HashSet::new();
+        let shas: Vec<String> = graph
+            .commits
+            .iter()
+            .take(limit)
+            .map(|info| info.oid)
+            .chain(
+                graph
+                    .branches
+                    .iter()
+                    .chain(&graph.tags)
+                    .map(|idx| graph.all_branches[*idx].target),
+            )
+            .filter(|oid| seen.insert(*oid))
+            .map(|oid| oid.to_string())
+            .collect();
+        let branches = self.merge_request_branches(None);
+        // Drop merge requests of commits that are no longer branch tips
+        let tips: HashSet<&String> = branches.values().flatten().collect();
+        self.merge_request_pipelines
+            .retain(|sha, _| tips.contains(sha));
+        self.graph_state
+            .merge_requests
+            .retain(|sha, _| tips.contains(sha));
+        for sha in shas {
             if self.pending_pipeline_requests.contains(&sha) {
                 continue;
             }
@@ -1163,6 +2150,152 @@ impl App {
 
             self.pending_pipeline_requests.insert(sha.clone());
             let _ = tx.send(PipelineRequest {
+                pipeline_id: self.pipeline_state.pinned_pipeline(&sha),
+                policy: self.pipeline_policy.clone(),
+                include_retried: self.pipeline_state.include_retried,
+                with_bridges: self.pipeline_state.current_sha.as_ref() == Some(&sha),
+                merge_request_pipelines: self.merge_request_pipelines_of(&sha),
+                sha,
+                base_url: base_url.clone(),
+                project_id: project_id.clone(),
+                token: token.clone(),
+            });
+        }
+
+        self.request_merge_request_pipelines(branches, base_url, project_id, token);
+    }
+
+    /// Source branches of merge requests with their tips: the local branches and the
+    /// branches of the CI remote, by name without the remote. Only the given names if any.
+    fn merge_request_branches(
+        &self,
+        names: Option<&HashSet<String>>,
+    ) -> HashMap<String, HashSet<String>> {
+        let graph = match &self.graph_state.graph {
+            Some(g) => g,
+            None => return HashMap::new(),
+        };
+        let remote_prefix = format!(
+            "{}/",
+            self.remote_info
+                .as_ref()
+                .and_then(|r| r.name.as_deref())
+                .unwrap_or("origin")
+        );
+        let mut branches: HashMap<String, HashSet<String>> = HashMap::new();
+        for branch in graph.branches.iter().map(|idx| &graph.all_branches[*idx]) {
+            let name = match (branch.is_remote, branch.name.strip_prefix(&remote_prefix)) {
+                (false, _) => branch.name.as_str(),
+                (true, Some(name)) if name != "HEAD" => name,
+                _ => continue,
+            };
+            if branch.is_tag
+                || names.is_some_and(|names| !names.contains(name) && !names.contains(&branch.name))
+            {
+                continue;
+            }
+            branches
+                .entry(name.to_string())
+                .or_default()
+                .insert(branch.target.to_string());
+        }
+        branches
+    }
+
+    fn request_merge_request_pipelines(
+        &mut self,
+        branches: HashMap<String, HashSet<String>>,
+        base_url: String,
+        project_id: String,
+        token: String,
+    ) {
+        if self.merge_requests_pending || branches.is_empty() {
+            return;
+        }
+        if let Some(tx) = &self.merge_request_tx {
+            let _ = tx.send(MergeRequestPipelinesRequest {
+                branches,
+                base_url,
+                project_id,
+                token,
+            });
+            self.merge_requests_pending = true;
+        }
+    }
+
+    fn merge_request_pipelines_of(&self, sha: &str) -> Vec<u64> {
+        self.merge_request_pipelines
+            .get(sha)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Attaches the pipelines of open merge requests to the source branch tips in the
+    /// response and reloads the pipelines of tips whose merge request pipelines changed.
+    pub fn handle_merge_request_pipelines_response(
+        &mut self,
+        response: MergeRequestPipelinesResponse,
+    ) {
+        self.merge_requests_pending = false;
+        // Only fails if no branch could be queried, e.g. when merge requests are
+        // disabled or not accessible with the token
+        let by_sha = match response.result {
+            Ok(by_sha) => by_sha,
+            Err(err) => {
+                self.set_error(format!("Can't load merge request pipelines: {}", err));
+                return;
+            }
+        };
+
+        let mut changed = vec![];
+        for (sha, pipelines) in by_sha {
+            match pipelines.iter().find_map(|p| p.merge_request_iid()) {
+                Some(iid) => self.graph_state.merge_requests.insert(sha.clone(), iid),
+                None => self.graph_state.merge_requests.remove(&sha),
+            };
+            let ids: Vec<u64> = pipelines.iter().map(|p| p.id).collect();
+            let old_ids = if ids.is_empty() {
+                self.merge_request_pipelines.remove(&sha)
+            } else {
+                self.merge_request_pipelines
+                    .insert(sha.clone(), ids.clone())
+            };
+            if old_ids.unwrap_or_default() != ids {
+                changed.push(sha);
+            }
+        }
+        if changed.is_empty() {
+            return;
+        }
+
+        let (base_url, project_id, host) = match &self.remote_info {
+            Some(r) if r.is_valid() => (
+                r.url.as_ref().unwrap().clone(),
+                r.project_id.as_ref().unwrap().clone(),
+                r.host.as_ref().unwrap().clone(),
+            ),
+            _ => return,
+        };
+        let token = match self.gitlab_config.get_token(&host) {
+            Some(t) => t.to_string(),
+            None => return,
+        };
+        let tx = match &self.pipeline_tx {
+            Some(tx) => tx,
+            None => return,
+        };
+        for sha in changed {
+            if self.pending_pipeline_requests.contains(&sha) {
+                continue;
+            }
+            self.pipeline_state.invalidate_cache(&sha);
+            self.pending_pipeline_requests.insert(sha.clone());
+            let _ = tx.send(PipelineRequest {
+                pipeline_id: self.pipeline_state.pinned_pipeline(&sha),
+                policy: self.pipeline_policy.clone(),
+                include_retried: self.pipeline_state.include_retried,
+                with_bridges: self.pipeline_state.current_sha.as_ref() == Some(&sha),
+                merge_request_pipelines: self.merge_request_pipelines_of(&sha),
                 sha,
                 base_url: base_url.clone(),
                 project_id: project_id.clone(),
@@ -1179,6 +2312,13 @@ impl App {
         if self.show_pipeline && (self.pipeline_state.is_running() || self.pipeline_state.loading) {
             return true;
         }
+        if self.history_state.as_ref().is_some_and(|s| s.loading)
+            || self.analytics_state.as_ref().is_some_and(|s| s.loading)
+            || self.lint_state.as_ref().is_some_and(|s| s.loading)
+            || self.compare_state.as_ref().is_some_and(|s| s.loading)
+        {
+            return true;
+        }
         self.graph_state
             .pipeline_statuses
             .values()
@@ -1229,6 +2369,543 @@ impl App {
         Ok(())
     }
 
+    pub fn select_remote(&mut self) -> Result<(), String> {
+        if let ActiveView::Remotes = self.active_view {
+        } else {
+            let graph = self
+                .graph_state
+                .graph
+                .as_ref()
+                .ok_or_else(|| "No repository loaded".to_string())?;
+            let remotes = RemoteInfo::list_from_repository(&graph.repository, &self.gitlab_config);
+            if remotes.is_empty() {
+                return Err("Repository has no remotes".to_string());
+            }
+            let current = self.remote_info.as_ref().and_then(|r| r.name.as_deref());
+            self.remotes_state = Some(RemoteListState::new(remotes, current));
+
+            let mut temp = ActiveView::Remotes;
+            std::mem::swap(&mut temp, &mut self.active_view);
+            self.prev_active_view = Some(temp);
+        }
+        Ok(())
+    }
+
+    /// Switches the CI remote to the one selected in the remote picker
+    /// and remembers the choice for this repository.
+    pub fn set_remote(&mut self) -> Result<(), String> {
+        let remote = match self
+            .remotes_state
+            .as_ref()
+            .and_then(|state| state.selected_remote())
+        {
+            Some(remote) => remote.clone(),
+            None => return Ok(()),
+        };
+        self.active_view = self.prev_active_view.take().unwrap_or(ActiveView::Graph);
+        self.remotes_state = None;
+
+        let name = remote.name.clone().unwrap_or_default();
+        if !remote.is_valid() {
+            return Err(format!(
+                "Unable to determine a GitLab project for remote '{}'",
+                name
+            ));
+        }
+
+        if let Some(graph) = &self.graph_state.graph {
+            self.gitlab_config
+                .set_remote(&repository_key(&graph.repository), &name);
+            self.gitlab_config.save()?;
+        }
+
+        self.remote_info = Some(remote);
+        self.graph_state.pipeline_statuses.clear();
+        self.pipeline_state.clear_cache();
+        self.pending_pipeline_requests.clear();
+
+        self.request_batch_pipelines();
+        if self.show_pipeline {
+            self.request_pipeline();
+        }
+        Ok(())
+    }
+
+    /// Branch whose pipeline history or analytics is shown: the one selected
+    /// in the branch list, or the checked-out branch.
+    fn selected_ref_name(&self) -> Option<String> {
+        let graph = self.graph_state.graph.as_ref()?;
+        if self.active_view == ActiveView::Branches {
+            let state = self.graph_state.branches.as_ref()?;
+            let index = state
+                .state
+                .selected()
+                .and_then(|sel| state.items[sel].index)?;
+            let branch = &graph.all_branches[index];
+            return if branch.is_remote {
+                branch
+                    .name
+                    .split_once('/')
+                    .map(|(_, name)| name.to_string())
+            } else {
+                Some(branch.name.clone())
+            };
+        }
+        if graph.head.is_branch {
+            Some(graph.head.name.clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn show_history(&mut self) -> Result<(), String> {
+        if let ActiveView::History = self.active_view {
+            return Ok(());
+        }
+
+        let (base_url, project_id, host) = match &self.remote_info {
+            Some(r) if r.is_valid() => (
+                r.url.as_ref().unwrap().clone(),
+                r.project_id.as_ref().unwrap().clone(),
+                r.host.as_ref().unwrap().clone(),
+            ),
+            _ => return Err("No GitLab remote found".to_string()),
+        };
+
+        let token = match self.gitlab_config.get_token(&host) {
+            Some(t) => t.to_string(),
+            None => {
+                self.open_gitlab_config();
+                return Ok(());
+            }
+        };
+
+        let ref_name = self.selected_ref_name().ok_or_else(|| {
+            "HEAD is detached. Select a branch in the branch list to show its pipelines."
+                .to_string()
+        })?;
+
+        self.history_state = Some(HistoryViewState::new(ref_name.clone()));
+        if let Some(tx) = &self.history_tx {
+            let _ = tx.send(HistoryRequest {
+                ref_name,
+                base_url,
+                project_id,
+                token,
+            });
+        }
+
+        let mut temp = ActiveView::History;
+        std::mem::swap(&mut temp, &mut self.active_view);
+        self.prev_active_view = Some(temp);
+        Ok(())
+    }
+
+    pub fn handle_history_response(&mut self, response: HistoryResponse) {
+        if let Some(state) = &mut self.history_state {
+            if state.ref_name == response.ref_name {
+                match response.result {
+                    Ok(pipelines) => state.set_pipelines(pipelines),
+                    Err(err) => state.set_error(err),
+                }
+            }
+        }
+    }
+
+    pub fn show_analytics(&mut self) -> Result<(), String> {
+        if let ActiveView::Analytics = self.active_view {
+            return Ok(());
+        }
+
+        let (base_url, project_id, host) = match &self.remote_info {
+            Some(r) if r.is_valid() => (
+                r.url.as_ref().unwrap().clone(),
+                r.project_id.as_ref().unwrap().clone(),
+                r.host.as_ref().unwrap().clone(),
+            ),
+            _ => return Err("No GitLab remote found".to_string()),
+        };
+
+        let token = match self.gitlab_config.get_token(&host) {
+            Some(t) => t.to_string(),
+            None => {
+                self.open_gitlab_config();
+                return Ok(());
+            }
+        };
+
+        let ref_name = self.selected_ref_name().ok_or_else(|| {
+            "HEAD is detached. Select a branch in the branch list to analyze its jobs.".to_string()
+        })?;
+
+        self.analytics_state = Some(AnalyticsViewState::new(ref_name.clone()));
+        if let Some(tx) = &self.analytics_tx {
+            let _ = tx.send(AnalyticsRequest {
+                ref_name,
+                base_url,
+                project_id,
+                token,
+            });
+        }
+
+        let mut temp = ActiveView::Analytics;
+        std::mem::swap(&mut temp, &mut self.active_view);
+        self.prev_active_view = Some(temp);
+        Ok(())
+    }
+
+    pub fn handle_analytics_response(&mut self, response: AnalyticsResponse) {
+        if let Some(state) = &mut self.analytics_state {
+            if state.ref_name == response.ref_name {
+                match response.result {
+                    Ok(analytics) => state.set_analytics(analytics),
+                    Err(err) => state.set_error(err),
+                }
+            }
+        }
+    }
+
+    /// Sends the selected commit's CI configuration to the CI Lint API, simulating
+    /// pipeline creation for the commit's branch.
+    pub fn show_lint(&mut self) -> Result<(), String> {
+        if let ActiveView::Lint = self.active_view {
+            return Ok(());
+        }
+
+        let (base_url, project_id, host) = match &self.remote_info {
+            Some(r) if r.is_valid() => (
+                r.url.as_ref().unwrap().clone(),
+                r.project_id.as_ref().unwrap().clone(),
+                r.host.as_ref().unwrap().clone(),
+            ),
+            _ => return Err("No GitLab remote found".to_string()),
+        };
+
+        let token = match self.gitlab_config.get_token(&host) {
+            Some(t) => t.to_string(),
+            None => {
+                self.open_gitlab_config();
+                return Ok(());
+            }
+        };
+
+        let graph = match &self.graph_state.graph {
+            Some(graph) => graph,
+            None => return Ok(()),
+        };
+        let oid = match self
+            .graph_state
+            .selected
+            .and_then(|idx| graph.commits.get(idx))
+        {
+            Some(info) => info.oid,
+            None => return Err("No commit selected".to_string()),
+        };
+        let tree = graph
+            .repository
+            .find_commit(oid)
+            .and_then(|commit| commit.tree())
+            .map_err(|err| err.message().to_string())?;
+        let entry = tree
+            .get_path(Path::new(CI_CONFIG_PATH))
+            .map_err(|_| format!("Commit has no {}", CI_CONFIG_PATH))?;
+        let blob = graph
+            .repository
+            .find_blob(entry.id())
+            .map_err(|err| err.message().to_string())?;
+        let content = std::str::from_utf8(blob.content())
+            .map_err(|_| format!("{} is not valid UTF-8", CI_CONFIG_PATH))?
+            .to_string();
+
+        let remote = self
+            .remote_info
+            .as_ref()
+            .and_then(|r| r.name.as_deref())
+            .unwrap_or("origin");
+        let ref_name = branch_at_commit(graph, oid).or_else(|| default_branch(graph, remote));
+
+        let sha = oid.to_string();
+        self.lint_state = Some(LintViewState::new(sha.clone(), ref_name.clone()));
+        if let Some(tx) = &self.lint_tx {
+            let _ = tx.send(LintRequest {
+                sha,
+                content,
+                ref_name,
+                base_url,
+                project_id,
+                token,
+            });
+        }
+
+        let mut temp = ActiveView::Lint;
+        std::mem::swap(&mut temp, &mut self.active_view);
+        self.prev_active_view = Some(temp);
+        Ok(())
+    }
+
+    pub fn handle_lint_response(&mut self, response: LintResponse) {
+        let color = self.color && self.diff_options.syntax_highlight;
+        if let Some(state) = &mut self.lint_state {
+            if state.sha == response.sha {
+                match response.result {
+                    Ok(result) => {
+                        let highlighted = if color {
+                            result
+                                .merged_yaml
+                                .as_ref()
+                                .and_then(|yaml| highlight(yaml, "yaml"))
+                        } else {
+                            None
+                        };
+                        state.set_result(result, highlighted)
+                    }
+                    Err(err) => state.set_error(err),
+                }
+            }
+        }
+    }
+
+    /// Compares the pipelines of the selected and the secondary selected commit job by job.
+    pub fn show_compare(&mut self) -> Result<(), String> {
+        if let ActiveView::Compare = self.active_view {
+            return Ok(());
+        }
+
+        let (base_url, project_id, host) = match &self.remote_info {
+            Some(r) if r.is_valid() => (
+                r.url.as_ref().unwrap().clone(),
+                r.project_id.as_ref().unwrap().clone(),
+                r.host.as_ref().unwrap().clone(),
+            ),
+            _ => return Err("No GitLab remote found".to_string()),
+        };
+
+        let token = match self.gitlab_config.get_token(&host) {
+            Some(t) => t.to_string(),
+            None => {
+                self.open_gitlab_config();
+                return Ok(());
+            }
+        };
+
+        let graph = match &self.graph_state.graph {
+            Some(graph) => graph,
+            None => return Ok(()),
+        };
+        let (selected, secondary) = match (
+            self.graph_state.selected,
+            self.graph_state.secondary_selected,
+        ) {
+            (Some(selected), Some(secondary)) if selected != secondary => (selected, secondary),
+            _ => return Err("Select a second commit to compare with (Ctrl + Up/Down)".to_string()),
+        };
+        // Commits are ordered newest first
+        let (head, base) = if selected < secondary {
+            (selected, secondary)
+        } else {
+            (secondary, selected)
+        };
+        let base_sha = graph.commits[base].oid.to_string();
+        let head_sha = graph.commits[head].oid.to_string();
+
+        self.compare_state = Some(CompareViewState::new(base_sha.clone(), head_sha.clone()));
+        if let Some(tx) = &self.compare_tx {
+            let _ = tx.send(CompareRequest {
+                base_merge_request_pipelines: self.merge_request_pipelines_of(&base_sha),
+                head_merge_request_pipelines: self.merge_request_pipelines_of(&head_sha),
+                base_sha,
+                head_sha,
+                policy: self.pipeline_policy.clone(),
+                base_url,
+                project_id,
+                token,
+            });
+        }
+
+        let mut temp = ActiveView::Compare;
+        std::mem::swap(&mut temp, &mut self.active_view);
+        self.prev_active_view = Some(temp);
+        Ok(())
+    }
+
+    pub fn handle_compare_response(&mut self, response: CompareResponse) {
+        if let Some(state) = &mut self.compare_state {
+            if state.base_sha == response.base_sha && state.head_sha == response.head_sha {
+                match response.result {
+                    Ok((Some(base), Some(head))) => state.set_pipelines(base, head),
+                    Ok((base, _)) => {
+                        let missing = if base.is_none() {
+                            &response.base_sha
+                        } else {
+                            &response.head_sha
+                        };
+                        state.set_error(format!("No pipeline for commit {}", &missing[..7]))
+                    }
+                    Err(err) => state.set_error(err),
+                }
+            }
+        }
+    }
+
+    /// Selects the commit of the pipeline selected in the history view and opens its pipeline.
+    pub fn select_history_pipeline(&mut self) -> Result<(), String> {
+        let sha = match self
+            .history_state
+            .as_ref()
+            .and_then(|state| state.selected_pipeline())
+        {
+            Some(pipeline) => pipeline.sha.clone(),
+            None => return Ok(()),
+        };
+
+        let graph = match &self.graph_state.graph {
+            Some(graph) => graph,
+            None => return Ok(()),
+        };
+        let index = Oid::from_str(&sha)
+            .ok()
+            .and_then(|oid| graph.indices.get(&oid).copied())
+            .ok_or_else(|| format!("Commit {} is not in the graph", &sha[..sha.len().min(7)]))?;
+
+        self.history_state = None;
+        self.prev_active_view = None;
+        self.active_view = ActiveView::Pipeline;
+        self.show_pipeline = true;
+        self.graph_state.selected = Some(index);
+        self.selection_changed()
+    }
+
+    /// Scans the loaded job log for errors with source locations.
+    pub fn show_problems(&mut self) -> Result<(), String> {
+        let state = &self.pipeline_state;
+        let job_name = match state.selected_job() {
+            Some(job) if state.job_log_job_id == Some(job.id) && !state.job_log.is_empty() => {
+                job.name.clone()
+            }
+            _ => return Err("No job log loaded".to_string()),
+        };
+        let sha = state
+            .details
+            .as_ref()
+            .and_then(|d| d.pipeline.as_ref())
+            .map(|p| p.sha.clone())
+            .ok_or_else(|| "No pipeline selected".to_string())?;
+
+        let lines: Vec<String> = state.job_log.iter().map(|l| l.plain_text()).collect();
+        let problems = find_problems(&lines, &default_matchers());
+
+        self.problems_state = Some(ProblemsViewState::new(job_name, sha, problems));
+        self.prev_active_view = Some(ActiveView::Pipeline);
+        self.active_view = ActiveView::Problems;
+        Ok(())
+    }
+
+    /// Opens the file of the selected problem at its line, at the pipeline's commit.
+    pub fn open_problem(&mut self) -> Result<(), String> {
+        let (sha, problem) = match self.problems_state.as_ref().and_then(|state| {
+            state
+                .selected_problem()
+                .map(|problem| (state.sha.clone(), problem.clone()))
+        }) {
+            Some(selected) => selected,
+            None => return Ok(()),
+        };
+        let short = &sha[..sha.len().min(7)];
+
+        let graph = match &self.graph_state.graph {
+            Some(graph) => graph,
+            None => return Ok(()),
+        };
+        let oid = Oid::from_str(&sha).map_err(|err| err.message().to_string())?;
+        let index = graph
+            .indices
+            .get(&oid)
+            .copied()
+            .ok_or_else(|| format!("Commit {} is not in the graph", short))?;
+        let path = {
+            let commit = graph
+                .repository
+                .find_commit(oid)
+                .map_err(|err| err.message().to_string())?;
+            let tree = commit.tree().map_err(|err| err.message().to_string())?;
+            resolve_tree_path(&tree, &problem.file)
+                .ok_or_else(|| format!("{} not found in commit {}", problem.file, short))?
+        };
+
+        self.problems_state = None;
+        self.prev_active_view = None;
+        self.show_pipeline = true;
+        self.graph_state.selected = Some(index);
+        self.selection_changed()?;
+        self.diff_options.diff_mode = DiffMode::New;
+
+        let listed = self.commit_state.content.as_mut().and_then(|content| {
+            let pos = content
+                .diffs
+                .items
+                .iter()
+                .position(|item| item.file == path)?;
+            content.diffs.state.select(Some(pos));
+            Some(pos)
+        });
+        if listed.is_some() {
+            self.file_changed(true)?;
+        } else {
+            self.diff_state.content = Some(self.unchanged_file_view(oid, &path)?);
+        }
+
+        if let Some(content) = &mut self.diff_state.content {
+            let header = content.diffs.first().map_or(0, |d| d.0.lines().count());
+            let line = header + problem.line.saturating_sub(1) as usize;
+            content.scroll = (line.saturating_sub(PROBLEM_CONTEXT_LINES) as u16, 0);
+            content.marked_line = Some(problem.line);
+        }
+        self.active_view = ActiveView::Diff;
+        Ok(())
+    }
+
+    /// Content of a file not changed by the commit, in the layout of `DiffMode::New`.
+    fn unchanged_file_view(&self, oid: Oid, path: &str) -> Result<DiffViewInfo, String> {
+        let graph = self
+            .graph_state
+            .graph
+            .as_ref()
+            .ok_or_else(|| "No repository".to_string())?;
+        let commit = graph
+            .repository
+            .find_commit(oid)
+            .map_err(|err| err.message().to_string())?;
+        let entry = commit
+            .tree()
+            .and_then(|tree| tree.get_path(Path::new(path)))
+            .map_err(|err| err.message().to_string())?;
+        let blob = graph
+            .repository
+            .find_blob(entry.id())
+            .map_err(|err| err.message().to_string())?;
+        let text = std::str::from_utf8(blob.content())
+            .unwrap_or("Invalid UTF8 character.")
+            .replace('\t', &self.settings.tab_spaces);
+
+        let highlighted = if self.color && self.diff_options.syntax_highlight {
+            Path::new(path)
+                .extension()
+                .and_then(|ext| ext.to_str().and_then(|ext| highlight(&text, ext)))
+        } else {
+            None
+        };
+        let diffs = vec![
+            (
+                format!("{} (unchanged in {})", path, &oid.to_string()[..7]),
+                None,
+                None,
+            ),
+            (text, None, None),
+        ];
+        let parent = commit.parent_id(0).unwrap_or_else(|_| Oid::zero());
+        Ok(DiffViewInfo::new(diffs, highlighted, oid, parent))
+    }
+
     pub fn select_head(&mut self) -> Result<(), String> {
         if let Some(graph) = &self.graph_state.graph {
             if let Some(index) = graph.indices.get(&graph.head.oid) {
@@ -1562,6 +3239,95 @@ fn get_file_diffs(
     Ok(diffs)
 }
 
+/// The branch CI rules are evaluated for: a branch pointing at the commit (preferably
+/// a local one), else the branch the commit was traced to.
+fn branch_at_commit(graph: &GitGraph, oid: Oid) -> Option<String> {
+    let info = graph.commits.get(*graph.indices.get(&oid)?)?;
+    let branches: Vec<&BranchInfo> = info
+        .branches
+        .iter()
+        .map(|idx| &graph.all_branches[*idx])
+        .filter(|b| !b.is_tag)
+        .collect();
+    let branch = branches
+        .iter()
+        .find(|b| !b.is_remote)
+        .or(branches.first())
+        .copied()
+        .or_else(|| info.branch_trace.map(|idx| &graph.all_branches[idx]))?;
+    if branch.is_remote {
+        branch
+            .name
+            .split_once('/')
+            .map(|(_, name)| name.to_string())
+    } else {
+        Some(branch.name.clone())
+    }
+}
+
+/// The remote's default branch, falling back to a local `main` or `master`.
+fn default_branch(graph: &GitGraph, remote: &str) -> Option<String> {
+    let prefix = format!("refs/remotes/{}/", remote);
+    let head = graph
+        .repository
+        .find_reference(&format!("{}HEAD", prefix))
+        .ok()
+        .and_then(|r| r.symbolic_target().map(str::to_string))
+        .and_then(|target| target.strip_prefix(&prefix).map(str::to_string));
+    head.or_else(|| {
+        ["main", "master"]
+            .into_iter()
+            .find(|name| {
+                graph
+                    .repository
+                    .find_branch(name, git2::BranchType::Local)
+                    .is_ok()
+            })
+            .map(str::to_string)
+    })
+}
+
+/// Finds a log path in the tree, dropping leading directories of absolute CI paths
+/// (e.g. `/builds/group/project/src/main.rs`) until it matches.
+fn resolve_tree_path(tree: &Tree, file: &str) -> Option<String> {
+    let parts: Vec<&str> = file.split('/').filter(|p| !p.is_empty()).collect();
+    (0..parts.len())
+        .map(|start| parts[start..].join("/"))
+        .find(|path| tree.get_path(Path::new(path)).is_ok())
+}
+
+/// Percent-encodes a URL path segment, e.g. a file or tag name.
+fn escape_path_segment(segment: &str) -> String {
+    url::form_urlencoded::byte_serialize(segment.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20")
+}
+
+/// The diff hunk containing the line, or the whole text if there are no hunks.
+fn diff_hunk_at(diffs: &DiffLines, line: usize) -> String {
+    let is_header = |(text, _, _): &(String, Option<u32>, Option<u32>)| text.starts_with("@@");
+    let start = diffs
+        .iter()
+        .take(line + 1)
+        .rposition(is_header)
+        .or_else(|| diffs.iter().position(is_header));
+    let range = match start {
+        Some(start) => {
+            let end = diffs[start + 1..]
+                .iter()
+                .position(|d| is_header(d) || d.0.starts_with("diff --git"))
+                .map(|pos| start + 1 + pos)
+                .unwrap_or(diffs.len());
+            start..end
+        }
+        None => 0..diffs.len(),
+    };
+    diffs[range]
+        .iter()
+        .map(|(text, _, _)| text.as_str())
+        .collect()
+}
+
 fn print_diff_line(_delta: &DiffDelta, _hunk: &Option<DiffHunk>, line: &DiffLine) -> String {
     let mut out = String::new();
     match line.origin() {
@@ -0,0 +1,555 @@
+//! Bisection kept in the same state as `git bisect`: `refs/bisect/*` and the
+//! `BISECT_*` files in the git directory, so it can be continued on the command line.
+
+use crate::gitlab::models::PipelineStatus;
+use git2::{Oid, Repository, Sort};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+
+/// Comment logged before the commits marked by `auto_mark`.
+const AUTO_MARK_COMMENT: &str = "auto-marked by pipeline status";
+
+/// Above this many candidates, the midpoint is taken from the middle of the topological
+/// order instead of counting the ancestors of every candidate.
+const MAX_EXACT_CANDIDATES: usize = 2048;
+
+const BISECT_FILES: [&str; 9] = [
+    "BISECT_START",
+    "BISECT_TERMS",
+    "BISECT_NAMES",
+    "BISECT_LOG",
+    "BISECT_EXPECTED_REV",
+    "BISECT_ANCESTORS_OK",
+    "BISECT_RUN",
+    "BISECT_HEAD",
+    "BISECT_FIRST_PARENT",
+];
+
+#[derive(Debug, Clone)]
+pub struct Bisect {
+    pub bad: Option<Oid>,
+    pub good: Vec<Oid>,
+    /// Commit to test next.
+    pub next: Option<Oid>,
+    /// Commits that may still be the first bad one, children first.
+    candidates: Vec<Oid>,
+    /// Commits marked by the user, which are never marked automatically.
+    manual: HashSet<Oid>,
+    range: HashSet<Oid>,
+    term_bad: String,
+    term_good: String,
+}
+
+impl Bisect {
+    fn with_terms(term_bad: &str, term_good: &str) -> Self {
+        Self {
+            bad: None,
+            good: vec![],
+            next: None,
+            candidates: vec![],
+            manual: HashSet::new(),
+            range: HashSet::new(),
+            term_bad: term_bad.to_string(),
+            term_good: term_good.to_string(),
+        }
+    }
+
+    /// Loads the bisection in progress, if any.
+    pub fn load(repo: &Repository) -> Result<Option<Self>, String> {
+        if !repo.path().join("BISECT_START").exists() {
+            return Ok(None);
+        }
+        let terms = fs::read_to_string(repo.path().join("BISECT_TERMS")).unwrap_or_default();
+        let mut terms = terms.lines();
+        let mut bisect = Self::with_terms(
+            terms.next().unwrap_or("bad"),
+            terms.next().unwrap_or("good"),
+        );
+
+        bisect.bad = repo
+            .refname_to_id(&format!("refs/bisect/{}", bisect.term_bad))
+            .ok();
+        let references = repo
+            .references_glob(&format!("refs/bisect/{}-*", bisect.term_good))
+            .map_err(|err| err.message().to_string())?;
+        for reference in references {
+            let reference = reference.map_err(|err| err.message().to_string())?;
+            if let Some(oid) = reference.target() {
+                bisect.good.push(oid);
+            }
+        }
+        // Marks not preceded by the auto-mark comment were made by the user
+        let log = fs::read_to_string(repo.path().join("BISECT_LOG")).unwrap_or_default();
+        let mut auto = false;
+        for line in log.lines() {
+            if line.strip_prefix("# ") == Some(AUTO_MARK_COMMENT) {
+                auto = true;
+            } else if let Some(args) = line.strip_prefix("git bisect ") {
+                let oid = args.split_whitespace().nth(1).map(Oid::from_str);
+                if let (Some(Ok(oid)), false) = (oid, auto) {
+                    bisect.manual.insert(oid);
+                }
+                auto = false;
+            }
+        }
+        bisect.update(repo)?;
+        Ok(Some(bisect))
+    }
+
+    /// Starts a bisection like `git bisect start`, remembering the current branch.
+    pub fn start(repo: &Repository) -> Result<Self, String> {
+        let head = repo.head().map_err(|err| err.message().to_string())?;
+        let start = match (head.is_branch(), head.shorthand(), head.target()) {
+            (true, Some(branch), _) => branch.to_string(),
+            (_, _, Some(oid)) => oid.to_string(),
+            _ => return Err("Can't bisect without a HEAD commit".to_string()),
+        };
+        write_file(repo, "BISECT_START", &format!("{}\n", start))?;
+        write_file(repo, "BISECT_TERMS", "bad\ngood\n")?;
+        write_file(repo, "BISECT_NAMES", "\n")?;
+        write_file(repo, "BISECT_LOG", "git bisect start\n")?;
+        Ok(Self::with_terms("bad", "good"))
+    }
+
+    /// Ends the bisection by removing `refs/bisect/*` and the `BISECT_*` files.
+    /// Unlike `git bisect reset`, HEAD is left where it is.
+    pub fn reset(repo: &Repository) -> Result<(), String> {
+        let names: Vec<String> = repo
+            .references_glob("refs/bisect/*")
+            .map_err(|err| err.message().to_string())?
+            .filter_map(|reference| reference.ok()?.name().map(str::to_string))
+            .collect();
+        for name in names {
+            repo.find_reference(&name)
+                .and_then(|mut reference| reference.delete())
+                .map_err(|err| err.message().to_string())?;
+        }
+        for file in BISECT_FILES {
+            match fs::remove_file(repo.path().join(file)) {
+                Err(err) if err.kind() != ErrorKind::NotFound => return Err(err.to_string()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks a commit good or bad, like `git bisect good|bad <commit>`.
+    pub fn mark(&mut self, repo: &Repository, oid: Oid, good: bool) -> Result<(), String> {
+        self.record(repo, oid, good, None)?;
+        self.manual.insert(oid);
+        self.update(repo)?;
+        self.log_first_bad(repo)
+    }
+
+    /// Marks commits in the range by their pipeline: the oldest failed one bad and the
+    /// newest successful one good, until no candidate with a result is left. Without a
+    /// good commit, the newest ancestor of the bad commit with a successful pipeline is
+    /// marked good first. The bad commit and commits marked by the user are left as
+    /// they are, e.g. a flaky pipeline that passed. Returns the number of commits marked.
+    pub fn auto_mark(
+        &mut self,
+        repo: &Repository,
+        statuses: &HashMap<String, PipelineStatus>,
+    ) -> Result<usize, String> {
+        let mut marked = 0;
+
+        if let (Some(bad), true) = (self.bad, self.good.is_empty()) {
+            let mut walk = repo.revwalk().map_err(|err| err.message().to_string())?;
+            walk.set_sorting(Sort::TOPOLOGICAL)
+                .and_then(|_| walk.push(bad))
+                .map_err(|err| err.message().to_string())?;
+            let good = walk
+                .filter_map(Result::ok)
+                .find(|oid| self.may_auto_mark(oid, statuses, PipelineStatus::Success));
+            if let Some(good) = good {
+                self.record(repo, good, true, Some(AUTO_MARK_COMMENT))?;
+                self.update(repo)?;
+                marked += 1;
+            }
+        }
+
+        loop {
+            let failed = self
+                .candidates
+                .iter()
+                .rev()
+                .find(|oid| self.may_auto_mark(oid, statuses, PipelineStatus::Failed));
+            let (oid, good) = match failed {
+                Some(oid) => (*oid, false),
+                None => match self
+                    .candidates
+                    .iter()
+                    .find(|oid| self.may_auto_mark(oid, statuses, PipelineStatus::Success))
+                {
+                    Some(oid) => (*oid, true),
+                    None => break,
+                },
+            };
+            self.record(repo, oid, good, Some(AUTO_MARK_COMMENT))?;
+            self.update(repo)?;
+            marked += 1;
+        }
+
+        if marked > 0 {
+            self.log_first_bad(repo)?;
+        }
+        Ok(marked)
+    }
+
+    /// Whether the commit's pipeline has the status, and it is neither the bad commit
+    /// nor marked by the user.
+    fn may_auto_mark(
+        &self,
+        oid: &Oid,
+        statuses: &HashMap<String, PipelineStatus>,
+        status: PipelineStatus,
+    ) -> bool {
+        Some(*oid) != self.bad
+            && !self.manual.contains(oid)
+            && statuses.get(&oid.to_string()) == Some(&status)
+    }
+
+    pub fn in_range(&self, oid: &Oid) -> bool {
+        self.range.contains(oid)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// Roughly how many more commits need to be tested.
+    pub fn steps_left(&self) -> u32 {
+        usize::BITS - self.candidates.len().saturating_sub(1).leading_zeros()
+    }
+
+    /// The first bad commit, once it is the only candidate left.
+    pub fn first_bad(&self) -> Option<Oid> {
+        if self.candidates.len() == 1 {
+            self.bad
+        } else {
+            None
+        }
+    }
+
+    pub fn is_good(&self, oid: &Oid) -> bool {
+        self.good.contains(oid)
+    }
+
+    fn record(
+        &mut self,
+        repo: &Repository,
+        oid: Oid,
+        good: bool,
+        comment: Option<&str>,
+    ) -> Result<(), String> {
+        let term = if good {
+            &self.term_good
+        } else {
+            &self.term_bad
+        };
+        let refname = if good {
+            format!("refs/bisect/{}-{}", term, oid)
+        } else {
+            format!("refs/bisect/{}", term)
+        };
+        repo.reference(&refname, oid, true, "bisect")
+            .map_err(|err| err.message().to_string())?;
+
+        let mut log = String::new();
+        if let Some(comment) = comment {
+            log.push_str(&format!("# {}\n", comment));
+        }
+        log.push_str(&format!(
+            "# {}: [{}] {}\ngit bisect {} {}\n",
+            term,
+            oid,
+            summary(repo, oid),
+            term,
+            oid
+        ));
+        append_file(repo, "BISECT_LOG", &log)?;
+
+        if good {
+            if !self.good.contains(&oid) {
+                self.good.push(oid);
+            }
+        } else {
+            self.bad = Some(oid);
+        }
+        Ok(())
+    }
+
+    fn log_first_bad(&self, repo: &Repository) -> Result<(), String> {
+        match self.first_bad() {
+            Some(oid) => append_file(
+                repo,
+                "BISECT_LOG",
+                &format!(
+                    "# first {} commit: [{}] {}\n",
+                    self.term_bad,
+                    oid,
+                    summary(repo, oid)
+                ),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Recomputes the candidates and the next commit to test. Both stay empty until a
+    /// bad and a good commit are known.
+    fn update(&mut self, repo: &Repository) -> Result<(), String> {
+        self.candidates.clear();
+        self.range.clear();
+        self.next = None;
+        let bad = match self.bad {
+            Some(bad) if !self.good.is_empty() => bad,
+            _ => return Ok(()),
+        };
+
+        let mut walk = repo.revwalk().map_err(|err| err.message().to_string())?;
+        walk.set_sorting(Sort::TOPOLOGICAL)
+            .and_then(|_| walk.push(bad))
+            .map_err(|err| err.message().to_string())?;
+        for good in &self.good {
+            walk.hide(*good).map_err(|err| err.message().to_string())?;
+        }
+        self.candidates = walk
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.message().to_string())?;
+        self.range = self.candidates.iter().copied().collect();
+        self.next = midpoint(repo, &self.candidates)?;
+        Ok(())
+    }
+}
+
+/// The candidate whose ancestors within the range split it most evenly, as picked by
+/// `git bisect`. Candidates are in topological order, children first.
+fn midpoint(repo: &Repository, candidates: &[Oid]) -> Result<Option<Oid>, String> {
+    let count = candidates.len();
+    if count <= 1 {
+        return Ok(None);
+    }
+    if count > MAX_EXACT_CANDIDATES {
+        return Ok(Some(candidates[count / 2]));
+    }
+
+    let index: HashMap<Oid, usize> = candidates
+        .iter()
+        .enumerate()
+        .map(|(idx, oid)| (*oid, idx))
+        .collect();
+    let words = count.div_ceil(64);
+    let mut reach = vec![0u64; count * words];
+    // Parents come after their children, so they are complete when a child is visited.
+    for idx in (0..count).rev() {
+        reach[idx * words + idx / 64] |= 1 << (idx % 64);
+        let commit = repo
+            .find_commit(candidates[idx])
+            .map_err(|err| err.message().to_string())?;
+        for parent in commit.parent_ids() {
+            if let Some(&parent_idx) = index.get(&parent) {
+                let (children, parents) = reach.split_at_mut(parent_idx * words);
+                let row = &mut children[idx * words..(idx + 1) * words];
+                for (word, parent_word) in row.iter_mut().zip(&parents[..words]) {
+                    *word |= parent_word;
+                }
+            }
+        }
+    }
+
+    let best = (0..count).max_by_key(|idx| {
+        let ancestors = reach[idx * words..(idx + 1) * words]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum::<usize>();
+        // Ties go to the newest candidate.
+        (ancestors.min(count - ancestors), count - idx)
+    });
+    Ok(best.map(|idx| candidates[idx]))
+}
+
+fn summary(repo: &Repository, oid: Oid) -> String {
+    repo.find_commit(oid)
+        .ok()
+        .and_then(|commit| commit.summary().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn write_file(repo: &Repository, name: &str, content: &str) -> Result<(), String> {
+    fs::write(repo.path().join(name), content).map_err(|err| err.to_string())
+}
+
+fn append_file(repo: &Repository, name: &str, content: &str) -> Result<(), String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(repo.path().join(name))
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    fn commit(repo: &Repository, message: &str, parents: &[Oid]) -> Oid {
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parents: Vec<_> = parents
+            .iter()
+            .map(|oid| repo.find_commit(*oid).unwrap())
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(None, &signature, &signature, message, &tree, &parents)
+            .unwrap()
+    }
+
+    /// A repository with the commits `c0` to `c{count - 1}` in a line, `master` at the last.
+    fn linear_repo(count: usize) -> (TempDir, Repository, Vec<Oid>) {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let mut commits: Vec<Oid> = vec![];
+        for idx in 0..count {
+            let parents: Vec<Oid> = commits.last().copied().into_iter().collect();
+            commits.push(commit(&repo, &format!("c{}", idx), &parents));
+        }
+        repo.reference("refs/heads/master", commits[count - 1], true, "test")
+            .unwrap();
+        repo.set_head("refs/heads/master").unwrap();
+        (dir, repo, commits)
+    }
+
+    fn pipelines(marks: &[(Oid, PipelineStatus)]) -> HashMap<String, PipelineStatus> {
+        marks
+            .iter()
+            .map(|(oid, status)| (oid.to_string(), *status))
+            .collect()
+    }
+
+    #[test]
+    fn midpoint_splits_the_range_in_half() {
+        let (_dir, repo, commits) = linear_repo(10);
+        // c9 to c1, children first
+        let candidates: Vec<Oid> = commits[1..].iter().rev().copied().collect();
+        // c4 and c5 split the range 4:5 and 5:4, ties go to the newest
+        assert_eq!(midpoint(&repo, &candidates).unwrap(), Some(commits[5]));
+        assert_eq!(midpoint(&repo, &candidates[..1]).unwrap(), None);
+        assert_eq!(midpoint(&repo, &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn midpoint_counts_ancestors_across_merges() {
+        let (_dir, repo, commits) = linear_repo(1);
+        let base = commits[0];
+        let a1 = commit(&repo, "a1", &[base]);
+        let a2 = commit(&repo, "a2", &[a1]);
+        let a3 = commit(&repo, "a3", &[a2]);
+        let b1 = commit(&repo, "b1", &[base]);
+        let merge = commit(&repo, "merge", &[a3, b1]);
+
+        let mut walk = repo.revwalk().unwrap();
+        walk.set_sorting(Sort::TOPOLOGICAL).unwrap();
+        walk.push(merge).unwrap();
+        walk.hide(base).unwrap();
+        let candidates: Vec<Oid> = walk.map(Result::unwrap).collect();
+        assert_eq!(candidates.len(), 5);
+        // a2 and a3 split the 5 candidates 2:3 and 3:2, ties go to the child
+        assert_eq!(midpoint(&repo, &candidates).unwrap(), Some(a3));
+    }
+
+    #[test]
+    fn finds_the_first_bad_commit() {
+        let (_dir, repo, commits) = linear_repo(10);
+        let mut bisect = Bisect::start(&repo).unwrap();
+        bisect.mark(&repo, commits[9], false).unwrap();
+        assert_eq!(bisect.first_bad(), None);
+        assert_eq!(bisect.next, None);
+        bisect.mark(&repo, commits[0], true).unwrap();
+        assert_eq!(bisect.remaining(), 9);
+
+        let mut steps = 0;
+        while bisect.first_bad().is_none() {
+            let next = bisect.next.unwrap();
+            let idx = commits.iter().position(|oid| *oid == next).unwrap();
+            bisect.mark(&repo, next, idx < 6).unwrap();
+            steps += 1;
+        }
+        assert_eq!(bisect.first_bad(), Some(commits[6]));
+        assert!(steps <= 4, "{} steps", steps);
+
+        let log = fs::read_to_string(repo.path().join("BISECT_LOG")).unwrap();
+        assert!(log.starts_with("git bisect start\n"));
+        assert!(log.contains(&format!("# first bad commit: [{}] c6\n", commits[6])));
+
+        let loaded = Bisect::load(&repo).unwrap().unwrap();
+        assert_eq!(loaded.first_bad(), Some(commits[6]));
+
+        Bisect::reset(&repo).unwrap();
+        assert!(Bisect::load(&repo).unwrap().is_none());
+        assert!(repo
+            .references_glob("refs/bisect/*")
+            .unwrap()
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn auto_mark_uses_pipeline_results() {
+        let (_dir, repo, commits) = linear_repo(10);
+        let mut bisect = Bisect::start(&repo).unwrap();
+        bisect.mark(&repo, commits[9], false).unwrap();
+
+        let statuses = pipelines(&[
+            (commits[2], PipelineStatus::Success),
+            (commits[3], PipelineStatus::Success),
+            (commits[6], PipelineStatus::Failed),
+            (commits[7], PipelineStatus::Failed),
+        ]);
+        assert_eq!(bisect.auto_mark(&repo, &statuses).unwrap(), 2);
+        assert_eq!(bisect.good, vec![commits[3]]);
+        assert_eq!(bisect.bad, Some(commits[6]));
+        assert_eq!(bisect.remaining(), 3);
+        assert_eq!(bisect.auto_mark(&repo, &statuses).unwrap(), 0);
+    }
+
+    #[test]
+    fn auto_mark_leaves_the_bad_commit_and_manual_marks_alone() {
+        let (_dir, repo, commits) = linear_repo(10);
+        let mut bisect = Bisect::start(&repo).unwrap();
+        // A flaky pipeline passed on the commit the user knows to be bad
+        bisect.mark(&repo, commits[9], false).unwrap();
+        let statuses = pipelines(&[
+            (commits[9], PipelineStatus::Success),
+            (commits[8], PipelineStatus::Success),
+            (commits[1], PipelineStatus::Success),
+        ]);
+        assert_eq!(bisect.auto_mark(&repo, &statuses).unwrap(), 1);
+        assert_eq!(bisect.bad, Some(commits[9]));
+        assert_eq!(bisect.good, vec![commits[8]]);
+        assert_eq!(bisect.first_bad(), Some(commits[9]));
+
+        // Marks of the user are known after loading the state again
+        let (_dir, repo, commits) = linear_repo(10);
+        let mut bisect = Bisect::start(&repo).unwrap();
+        bisect.mark(&repo, commits[9], false).unwrap();
+        bisect.mark(&repo, commits[6], false).unwrap();
+        let mut loaded = Bisect::load(&repo).unwrap().unwrap();
+        let statuses = pipelines(&[
+            (commits[6], PipelineStatus::Success),
+            (commits[3], PipelineStatus::Success),
+        ]);
+        assert_eq!(loaded.auto_mark(&repo, &statuses).unwrap(), 1);
+        assert_eq!(loaded.bad, Some(commits[6]));
+        assert_eq!(loaded.good, vec![commits[3]]);
+        let log = fs::read_to_string(repo.path().join("BISECT_LOG")).unwrap();
+        assert!(log.contains(&format!(
+            "# {}\n# good: [{}] c3\ngit bisect good {}\n",
+            AUTO_MARK_COMMENT, commits[3], commits[3]
+        )));
+    }
+}
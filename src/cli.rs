@@ -0,0 +1,431 @@
+//! Headless commands that print to stdout instead of starting the UI.
+
+use crate::gitlab::models::{Job, Pipeline, PipelineDetails, PipelinePolicy, PipelineStatus};
+use crate::gitlab::GitLabClient;
+use crate::gitlab_config::{GitLabConfig, RemoteInfo};
+use crate::widgets::pipeline_view::{format_duration, PipelineViewState};
+use git2::Repository;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long `watch` waits for a pipeline to be created for a freshly pushed commit.
+const PIPELINE_APPEAR_TIMEOUT: Duration = Duration::from_secs(120);
+/// How many failed requests in a row `watch` tolerates, e.g. while the network is down.
+const MAX_WATCH_RETRIES: u32 = 5;
+
+/// Exit code of `pipeline` commands that could not determine the pipeline's result,
+/// e.g. for a missing token or an unreachable server. Failed pipelines exit with 1.
+pub const EXIT_ERROR: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineAction {
+    Status,
+    Jobs,
+    Log,
+    Watch,
+}
+
+impl FromStr for PipelineAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "status" => Ok(Self::Status),
+            "jobs" => Ok(Self::Jobs),
+            "log" => Ok(Self::Log),
+            "watch" => Ok(Self::Watch),
+            other => Err(format!(
+                "Unknown pipeline command '{}'. Supports [status|jobs|log|watch].",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "Unknown output format '{}'. Supports [text|json].",
+                other
+            )),
+        }
+    }
+}
+
+pub struct PipelineCommand {
+    pub action: PipelineAction,
+    /// Revision whose pipeline is shown, e.g. `HEAD` or a branch name.
+    pub rev: String,
+    pub format: OutputFormat,
+    /// Job name or ID for `log`. Defaults to the first failed job, or the last job.
+    pub job: Option<String>,
+    /// Poll interval for `watch`.
+    pub interval: Duration,
+    /// Keep ANSI colors in job logs.
+    pub colored: bool,
+    /// Selects the pipeline of commits with several pipelines.
+    pub policy: PipelinePolicy,
+}
+
+/// Runs a `pipeline` subcommand. Returns the process exit code: 1 if a watched pipeline
+/// did not succeed.
+pub fn run_pipeline_command(repo: &Repository, command: &PipelineCommand) -> Result<i32, String> {
+    let config = GitLabConfig::load()?;
+    let remote = RemoteInfo::from_repository(repo, &config);
+    if !remote.is_valid() {
+        return Err("No GitLab remote found".to_string());
+    }
+    let host = remote.host.as_ref().unwrap();
+    let token = config.get_token(host).ok_or_else(|| {
+        format!(
+            "No GitLab token for {}. Start git-igitt and open the pipeline panel to enter one, or add it to {}",
+            host,
+            GitLabConfig::config_path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|_| "gitlab.toml".to_string())
+        )
+    })?;
+    let client = GitLabClient::new(remote.url.as_ref().unwrap(), token)?;
+    let project_id = remote.project_id.as_ref().unwrap();
+
+    let sha = repo
+        .revparse_single(&command.rev)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|err| format!("Invalid revision '{}': {}", command.rev, err.message()))?
+        .id()
+        .to_string();
+
+    if command.action == PipelineAction::Watch {
+        return watch(&client, project_id, &sha, command);
+    }
+
+    let details = fetch_details(&client, project_id, &sha, &command.policy)?
+        .ok_or_else(|| format!("No pipeline for commit {}", &sha[..7]))?;
+    let pipeline = details.pipeline.as_ref().unwrap();
+
+    match command.action {
+        PipelineAction::Status => print_status(pipeline, command.format)?,
+        PipelineAction::Jobs => print_jobs(&details, command.format)?,
+        PipelineAction::Log => {
+            let job = select_job(&details, command.job.as_deref())?;
+            let trace = client.get_job_trace(project_id, job.id)?;
+            print_log(job, &trace, command)?;
+        }
+        PipelineAction::Watch => unreachable!(),
+    }
+    Ok(0)
+}
+
+/// The pipeline of a commit selected by the policy with all its jobs. Fetched by ID as
+/// pipeline lists lack the duration.
+fn fetch_details(
+    client: &GitLabClient,
+    project_id: &str,
+    sha: &str,
+    policy: &PipelinePolicy,
+) -> Result<Option<PipelineDetails>, String> {
+    let pipelines = client.get_pipelines_for_commit(project_id, sha)?;
+    let id = match policy.select(&pipelines) {
+        Some(pipeline) => pipeline.id,
+        None => return Ok(None),
+    };
+    let pipeline = client.get_pipeline(project_id, id)?;
+    let mut jobs = client.get_pipeline_jobs(project_id, id, false)?;
+    jobs.extend(client.get_pipeline_bridges(project_id, id)?);
+    Ok(Some(PipelineDetails::from_jobs(pipeline, jobs)))
+}
+
+fn jobs(details: &PipelineDetails) -> impl Iterator<Item = &Job> {
+    details.stages.iter().flat_map(|s| &s.jobs)
+}
+
+fn select_job<'a>(details: &'a PipelineDetails, job: Option<&str>) -> Result<&'a Job, String> {
+    match job {
+        Some(job) => match jobs(details).find(|j| j.name == job || j.id.to_string() == job) {
+            Some(found) if found.bridge => Err(format!("'{}' is a trigger job without a log", job)),
+            Some(found) => Ok(found),
+            None => Err(format!("No job '{}' in the pipeline", job)),
+        },
+        None => jobs(details)
+            .find(|j| j.status == PipelineStatus::Failed && !j.bridge)
+            .or_else(|| jobs(details).filter(|j| !j.bridge).last())
+            .ok_or_else(|| "The pipeline has no jobs".to_string()),
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|err| format!("Failed to write JSON: {}", err))
+}
+
+fn format_secs(duration: Option<f64>) -> String {
+    duration
+        .map(|d| format_duration(d.round() as u64))
+        .unwrap_or_else(|| "--:--".to_string())
+}
+
+fn print_status(pipeline: &Pipeline, format: OutputFormat) -> Result<(), String> {
+    match format {
+        OutputFormat::Json => println!("{}", to_json(pipeline)?),
+        OutputFormat::Text => println!(
+            "Pipeline #{} {} {} ({}, {}) {}",
+            pipeline.id,
+            pipeline.status.symbol(),
+            pipeline.status,
+            pipeline.ref_name.as_deref().unwrap_or("-"),
+            format_secs(pipeline.duration.map(|d| d as f64)),
+            pipeline.web_url.as_deref().unwrap_or("")
+        ),
+    }
+    Ok(())
+}
+
+fn print_jobs(details: &PipelineDetails, format: OutputFormat) -> Result<(), String> {
+    match format {
+        OutputFormat::Json => {
+            let jobs: Vec<&Job> = jobs(details).collect();
+            println!("{}", to_json(&jobs)?);
+        }
+        OutputFormat::Text => {
+            let name_width = jobs(details).map(|j| j.name.len()).max().unwrap_or(0);
+            let stage_width = details
+                .stages
+                .iter()
+                .map(|s| s.name.len())
+                .max()
+                .unwrap_or(0);
+            for job in jobs(details) {
+                println!(
+                    "{:<sw$}  {:<nw$}  {} {:<9}  {}{}",
+                    job.stage,
+                    job.name,
+                    job.status.symbol(),
+                    job.status.to_string(),
+                    format_secs(job.duration),
+                    if job.allow_failure.unwrap_or(false) {
+                        "  (allowed to fail)"
+                    } else {
+                        ""
+                    },
+                    sw = stage_width,
+                    nw = name_width,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_log(job: &Job, trace: &str, command: &PipelineCommand) -> Result<(), String> {
+    let mut log = PipelineViewState::default();
+    log.set_job_log(job.id, trace);
+    match command.format {
+        OutputFormat::Json => println!(
+            "{}",
+            to_json(&serde_json::json!({
+                "job": job,
+                "log": log.job_log_export_text(false),
+            }))?
+        ),
+        OutputFormat::Text => print!("{}", log.job_log_export_text(command.colored)),
+    }
+    Ok(())
+}
+
+/// Exit code of `watch` for a finished pipeline: 0 unless it did not succeed.
+fn exit_code(status: PipelineStatus) -> i32 {
+    match status {
+        PipelineStatus::Success | PipelineStatus::Skipped | PipelineStatus::Manual => 0,
+        _ => 1,
+    }
+}
+
+/// Whether a failed request may succeed when repeated: network and server errors, but
+/// not e.g. an invalid token.
+fn is_transient(err: &str) -> bool {
+    match err.strip_prefix("GitLab API error: ") {
+        Some(status) => !status.starts_with('4') || status.starts_with("429"),
+        None => true,
+    }
+}
+
+/// Polls the pipeline until it finishes, printing job status changes. Failed requests
+/// are retried at the poll interval.
+fn watch(
+    client: &GitLabClient,
+    project_id: &str,
+    sha: &str,
+    command: &PipelineCommand,
+) -> Result<i32, String> {
+    let start = Instant::now();
+    let mut job_states: HashMap<u64, PipelineStatus> = HashMap::new();
+    let mut pipeline_state = None;
+    let mut waiting_printed = false;
+    let mut retries = 0;
+
+    loop {
+        let details = match fetch_details(client, project_id, sha, &command.policy) {
+            Err(err) if retries < MAX_WATCH_RETRIES && is_transient(&err) => {
+                retries += 1;
+                eprintln!("{} (retry {}/{})", err, retries, MAX_WATCH_RETRIES);
+                thread::sleep(command.interval);
+                continue;
+            }
+            Err(err) => return Err(err),
+            Ok(details) => {
+                retries = 0;
+                details
+            }
+        };
+        let details = match details {
+            Some(details) => details,
+            None if start.elapsed() < PIPELINE_APPEAR_TIMEOUT => {
+                if !waiting_printed && command.format == OutputFormat::Text {
+                    eprintln!("Waiting for a pipeline for commit {}...", &sha[..7]);
+                    waiting_printed = true;
+                }
+                thread::sleep(command.interval);
+                continue;
+            }
+            None => return Err(format!("No pipeline for commit {}", &sha[..7])),
+        };
+        let pipeline = details.pipeline.as_ref().unwrap();
+
+        if command.format == OutputFormat::Text {
+            if pipeline_state != Some(pipeline.status) {
+                print_status(pipeline, OutputFormat::Text)?;
+            }
+            for job in jobs(&details) {
+                if job_states.insert(job.id, job.status) != Some(job.status) {
+                    println!(
+                        "  {} {:<9} {}",
+                        job.status.symbol(),
+                        job.status.to_string(),
+                        job.name
+                    );
+                }
+            }
+        }
+        pipeline_state = Some(pipeline.status);
+
+        if pipeline.status.is_finished() {
+            if command.format == OutputFormat::Json {
+                println!(
+                    "{}",
+                    to_json(&serde_json::json!({
+                        "pipeline": pipeline,
+                        "jobs": jobs(&details).collect::<Vec<_>>(),
+                    }))?
+                );
+            }
+            return Ok(exit_code(pipeline.status));
+        }
+        thread::sleep(command.interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gitlab::models::Stage;
+
+    fn job(id: u64, name: &str, status: &str, bridge: bool) -> Job {
+        let mut job: Job = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": name,
+            "status": status,
+            "stage": "test",
+        }))
+        .unwrap();
+        job.bridge = bridge;
+        job
+    }
+
+    fn details(jobs: Vec<Job>) -> PipelineDetails {
+        let mut stage = Stage::new("test".to_string());
+        stage.jobs = jobs;
+        PipelineDetails {
+            stages: vec![stage],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_actions_and_formats() {
+        assert_eq!("watch".parse(), Ok(PipelineAction::Watch));
+        assert_eq!("log".parse(), Ok(PipelineAction::Log));
+        assert!("wait".parse::<PipelineAction>().is_err());
+        assert_eq!("json".parse(), Ok(OutputFormat::Json));
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn maps_finished_pipelines_to_exit_codes() {
+        let cases = [
+            (PipelineStatus::Success, 0),
+            (PipelineStatus::Skipped, 0),
+            (PipelineStatus::Manual, 0),
+            (PipelineStatus::Failed, 1),
+            (PipelineStatus::Canceled, 1),
+        ];
+        for (status, code) in cases {
+            assert_eq!(exit_code(status), code, "{:?}", status);
+        }
+    }
+
+    #[test]
+    fn retries_only_transient_errors() {
+        let cases = [
+            ("Request failed: connection refused", true),
+            ("GitLab API error: 500 Internal Server Error", true),
+            ("GitLab API error: 502 Bad Gateway", true),
+            ("GitLab API error: 429 Too Many Requests", true),
+            ("GitLab API error: 401 Unauthorized", false),
+            ("GitLab API error: 404 Not Found", false),
+        ];
+        for (err, transient) in cases {
+            assert_eq!(is_transient(err), transient, "{}", err);
+        }
+    }
+
+    #[test]
+    fn selects_the_job_to_show_the_log_of() {
+        let pipeline = details(vec![
+            job(1, "build", "success", false),
+            job(2, "deploy", "failed", true),
+            job(3, "test", "failed", false),
+            job(4, "lint", "success", false),
+            job(5, "trigger", "success", true),
+        ]);
+        // The first failed job with a log, by default
+        assert_eq!(select_job(&pipeline, None).unwrap().id, 3);
+        assert_eq!(select_job(&pipeline, Some("lint")).unwrap().id, 4);
+        assert_eq!(select_job(&pipeline, Some("1")).unwrap().id, 1);
+        assert!(select_job(&pipeline, Some("trigger"))
+            .unwrap_err()
+            .contains("trigger job"));
+        assert!(select_job(&pipeline, Some("missing"))
+            .unwrap_err()
+            .contains("No job"));
+
+        // Without a failure, the last job with a log
+        let pipeline = details(vec![
+            job(1, "build", "success", false),
+            job(2, "trigger", "success", true),
+        ]);
+        assert_eq!(select_job(&pipeline, None).unwrap().id, 1);
+        assert!(select_job(&details(vec![]), None).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+use super::models::{Job, Pipeline, PipelineStatus};
+use std::collections::{HashMap, HashSet};
+
+/// Aggregated durations and outcomes of one job across several pipelines.
+#[derive(Debug, Clone)]
+pub struct JobStats {
+    pub name: String,
+    pub stage: String,
+    pub runs: usize,
+    pub failures: usize,
+    pub p50: Option<f64>,
+    pub p95: Option<f64>,
+    /// Number of commits on which the job both failed and succeeded (e.g. after a retry).
+    pub flaky_shas: usize,
+    pub allow_failure: bool,
+}
+
+impl JobStats {
+    pub fn is_flaky(&self) -> bool {
+        self.flaky_shas > 0
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JobAnalytics {
+    pub pipelines: usize,
+    pub jobs: Vec<JobStats>,
+}
+
+impl JobAnalytics {
+    /// Aggregates the jobs (including retried ones) of several pipelines.
+    /// Flaky jobs come first, then the slowest by p95.
+    pub fn from_runs(runs: &[(Pipeline, Vec<Job>)]) -> Self {
+        struct Acc<'a> {
+            stage: &'a str,
+            runs: usize,
+            failures: usize,
+            durations: Vec<f64>,
+            failed_shas: HashSet<&'a str>,
+            passed_shas: HashSet<&'a str>,
+            allow_failure: bool,
+        }
+
+        let mut order: Vec<&str> = vec![];
+        let mut acc: HashMap<&str, Acc> = HashMap::new();
+
+        for (pipeline, jobs) in runs {
+            for job in jobs {
+                let entry = acc.entry(&job.name).or_insert_with(|| {
+                    order.push(&job.name);
+                    Acc {
+                        stage: &job.stage,
+                        runs: 0,
+                        failures: 0,
+                        durations: vec![],
+                        failed_shas: HashSet::new(),
+                        passed_shas: HashSet::new(),
+                        allow_failure: false,
+                    }
+                });
+                match job.status {
+                    PipelineStatus::Success => {
+                        entry.passed_shas.insert(&pipeline.sha);
+                    }
+                    PipelineStatus::Failed => {
+                        entry.failures += 1;
+                        entry.failed_shas.insert(&pipeline.sha);
+                    }
+                    _ => continue,
+                }
+                entry.runs += 1;
+                entry.allow_failure |= job.allow_failure.unwrap_or(false);
+                if let Some(duration) = job.duration {
+                    entry.durations.push(duration);
+                }
+            }
+        }
+
+        let mut jobs: Vec<_> = order
+            .into_iter()
+            .filter_map(|name| {
+                let mut a = acc.remove(name)?;
+                if a.runs == 0 {
+                    return None;
+                }
+                a.durations.sort_by(|x, y| x.total_cmp(y));
+                Some(JobStats {
+                    name: name.to_string(),
+                    stage: a.stage.to_string(),
+                    runs: a.runs,
+                    failures: a.failures,
+                    p50: percentile(&a.durations, 0.5),
+                    p95: percentile(&a.durations, 0.95),
+                    flaky_shas: a.failed_shas.intersection(&a.passed_shas).count(),
+                    allow_failure: a.allow_failure,
+                })
+            })
+            .collect();
+
+        jobs.sort_by(|a, b| {
+            b.is_flaky()
+                .cmp(&a.is_flaky())
+                .then_with(|| b.p95.unwrap_or(0.0).total_cmp(&a.p95.unwrap_or(0.0)))
+        });
+
+        Self {
+            pipelines: runs.len(),
+            jobs,
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted values.
+fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline(id: u64, sha: &str) -> Pipeline {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "status": "success",
+            "sha": sha,
+        }))
+        .unwrap()
+    }
+
+    fn job(name: &str, status: &str, duration: f64) -> Job {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": name,
+            "status": status,
+            "stage": "test",
+            "duration": duration,
+        }))
+        .unwrap()
+    }
+
+    fn stats<'a>(analytics: &'a JobAnalytics, name: &str) -> &'a JobStats {
+        analytics.jobs.iter().find(|j| j.name == name).unwrap()
+    }
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        assert_eq!(percentile(&[], 0.5), None);
+        assert_eq!(percentile(&[7.0], 0.5), Some(7.0));
+        assert_eq!(percentile(&[7.0], 0.95), Some(7.0));
+        // Odd count: rank ceil(0.5 * 5) = 3
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0, 5.0], 0.5), Some(3.0));
+        // Even count: rank 0.5 * 4 = 2, the lower middle value
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 0.5), Some(2.0));
+        let twenty: Vec<f64> = (1..=20).map(f64::from).collect();
+        // Rank 0.95 * 20 = 19 exactly, and ceil(0.95 * 21) = 20
+        assert_eq!(percentile(&twenty, 0.95), Some(19.0));
+        let twenty_one: Vec<f64> = (1..=21).map(f64::from).collect();
+        assert_eq!(percentile(&twenty_one, 0.95), Some(20.0));
+        assert_eq!(percentile(&[1.0, 2.0], 0.0), Some(1.0));
+        assert_eq!(percentile(&[1.0, 2.0], 1.0), Some(2.0));
+    }
+
+    #[test]
+    fn aggregates_nothing_without_runs() {
+        let analytics = JobAnalytics::from_runs(&[]);
+        assert_eq!(analytics.pipelines, 0);
+        assert!(analytics.jobs.is_empty());
+
+        // Jobs that never finished are left out
+        let runs = vec![(pipeline(1, "a"), vec![job("test", "running", 5.0)])];
+        let analytics = JobAnalytics::from_runs(&runs);
+        assert_eq!(analytics.pipelines, 1);
+        assert!(analytics.jobs.is_empty());
+    }
+
+    #[test]
+    fn aggregates_a_single_run() {
+        let runs = vec![(pipeline(1, "a"), vec![job("test", "failed", 42.0)])];
+        let analytics = JobAnalytics::from_runs(&runs);
+        let test = stats(&analytics, "test");
+        assert_eq!((test.runs, test.failures), (1, 1));
+        assert_eq!((test.p50, test.p95), (Some(42.0), Some(42.0)));
+        assert!(!test.is_flaky());
+    }
+
+    #[test]
+    fn counts_retried_failures_that_passed_as_flaky() {
+        let runs = vec![
+            (
+                pipeline(1, "a"),
+                vec![
+                    // Retried on the same commit
+                    job("test", "failed", 10.0),
+                    job("test", "success", 20.0),
+                    job("build", "success", 100.0),
+                ],
+            ),
+            (
+                pipeline(2, "b"),
+                vec![job("test", "success", 30.0), job("build", "failed", 90.0)],
+            ),
+            (
+                pipeline(3, "c"),
+                vec![job("test", "success", 40.0), job("build", "success", 80.0)],
+            ),
+        ];
+        let analytics = JobAnalytics::from_runs(&runs);
+        assert_eq!(analytics.pipelines, 3);
+
+        let test = stats(&analytics, "test");
+        assert_eq!((test.runs, test.failures, test.flaky_shas), (4, 1, 1));
+        assert_eq!((test.p50, test.p95), (Some(20.0), Some(40.0)));
+        // Failed and passed on different commits
+        let build = stats(&analytics, "build");
+        assert_eq!((build.runs, build.failures, build.flaky_shas), (3, 1, 0));
+
+        // Flaky jobs first, despite the lower p95
+        let names: Vec<&str> = analytics.jobs.iter().map(|j| j.name.as_str()).collect();
+        assert_eq!(names, vec!["test", "build"]);
+    }
+}
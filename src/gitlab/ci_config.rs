@@ -0,0 +1,1213 @@
+//! Offline preview of the pipeline a commit's `.gitlab-ci.yml` would create.
+//!
+//! Supports local `include:`s, `extends:`, YAML anchors and merge keys, `rules:` (`if`,
+//! `changes`, `exists`, `when`, `allow_failure`), `only`/`except` and `workflow:rules`,
+//! evaluated for a push pipeline on a branch. Remote, project and template includes are
+//! skipped; undecidable `if` expressions are assumed to match.
+
+use super::models::{Job, PipelineDetails, PipelineStatus, Stage};
+use git2::{Commit, ObjectType, Oid, Repository, Tree, TreeWalkMode, TreeWalkResult};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+pub const CI_CONFIG_PATH: &str = ".gitlab-ci.yml";
+
+const DEFAULT_STAGES: &[&str] = &["build", "test", "deploy"];
+const DEFAULT_JOB_STAGE: &str = "test";
+
+/// Top-level keys that are not jobs.
+const RESERVED_KEYS: &[&str] = &[
+    "after_script",
+    "before_script",
+    "cache",
+    "default",
+    "image",
+    "include",
+    "services",
+    "spec",
+    "stages",
+    "types",
+    "variables",
+    "workflow",
+];
+
+const MAX_INCLUDE_DEPTH: usize = 100;
+const MAX_EXTENDS_DEPTH: usize = 11;
+
+/// The ref a preview pipeline is evaluated for.
+#[derive(Debug, Clone, Default)]
+pub struct PreviewRef {
+    pub branch: Option<String>,
+    pub default_branch: Option<String>,
+}
+
+/// Builds the stages and jobs `.gitlab-ci.yml` of the commit would create.
+/// Returns `None` if the commit has no CI configuration.
+pub fn preview_pipeline(
+    repo: &Repository,
+    oid: Oid,
+    target: &PreviewRef,
+) -> Result<Option<PipelineDetails>, String> {
+    let commit = repo
+        .find_commit(oid)
+        .map_err(|err| err.message().to_string())?;
+    let tree = commit.tree().map_err(|err| err.message().to_string())?;
+
+    let mut config = match read_yaml(repo, &tree, CI_CONFIG_PATH)? {
+        Some(config) => config,
+        None => return Ok(None),
+    };
+    let mut files = TreeFiles::new(&tree);
+    resolve_includes(repo, &tree, &mut files, &mut config, 0)?;
+    let config = match config {
+        Value::Mapping(config) => config,
+        _ => return Err(format!("{} is not a mapping", CI_CONFIG_PATH)),
+    };
+
+    let mut eval = Evaluator::new(repo, &commit, &mut files, target, &config);
+
+    let mut details = PipelineDetails {
+        pipeline: None,
+        stages: vec![],
+        preview: true,
+        pipelines: vec![],
+        with_bridges: true,
+    };
+    if let Some(rules) = config.get("workflow").and_then(|w| w.get("rules")) {
+        match eval.rules(rules, &Mapping::new()) {
+            Some((when, _)) if when != "never" => {}
+            _ => return Ok(Some(details)),
+        }
+    }
+
+    let mut stage_names: Vec<String> = match config.get("stages").or(config.get("types")) {
+        Some(Value::Sequence(stages)) => stages
+            .iter()
+            .filter_map(|s| s.as_str().map(str::to_string))
+            .collect(),
+        _ => DEFAULT_STAGES.iter().map(|s| s.to_string()).collect(),
+    };
+    stage_names.retain(|s| s != ".pre" && s != ".post");
+    stage_names.insert(0, ".pre".to_string());
+    stage_names.push(".post".to_string());
+
+    let mut stages: Vec<Stage> = stage_names.into_iter().map(Stage::new).collect();
+    for (name, job) in &config {
+        let name = match name.as_str() {
+            Some(name) if !name.starts_with('.') && !RESERVED_KEYS.contains(&name) => name,
+            _ => continue,
+        };
+        if !job.is_mapping() {
+            continue;
+        }
+        let job = resolve_extends(&config, name, 0)?;
+        let (status, allow_failure) = match eval.job(&job) {
+            Some(result) => result,
+            None => continue,
+        };
+
+        let stage_name = job
+            .get("stage")
+            .and_then(Value::as_str)
+            .unwrap_or(DEFAULT_JOB_STAGE);
+        let stage = match stages.iter().position(|s| s.name == stage_name) {
+            Some(pos) => &mut stages[pos],
+            None => {
+                stages.push(Stage::new(stage_name.to_string()));
+                stages.last_mut().unwrap()
+            }
+        };
+        let tags: Vec<String> = job
+            .get("tags")
+            .and_then(Value::as_sequence)
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(|t| t.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        for job_name in parallel_names(name, job.get("parallel")) {
+            stage.jobs.push(Job {
+                id: 0,
+                name: job_name,
+                status,
+                stage: stage_name.to_string(),
+                web_url: None,
+                started_at: None,
+                finished_at: None,
+                duration: None,
+                allow_failure: Some(allow_failure),
+                queued_duration: None,
+                failure_reason: None,
+                runner: None,
+                user: None,
+                tag_list: tags.clone(),
+                retried: None,
+                coverage: None,
+                artifacts: vec![],
+                bridge: false,
+            });
+        }
+    }
+
+    stages.retain(|s| !s.jobs.is_empty());
+    let mut id = 0;
+    for job in stages.iter_mut().flat_map(|s| s.jobs.iter_mut()) {
+        id += 1;
+        job.id = id;
+    }
+    details.stages = stages;
+    Ok(Some(details))
+}
+
+/// Parses a YAML file of the tree. If the file has a `spec:` header document, the last
+/// document is the configuration.
+fn read_yaml(repo: &Repository, tree: &Tree, path: &str) -> Result<Option<Value>, String> {
+    let entry = match tree.get_path(Path::new(path)) {
+        Ok(entry) => entry,
+        Err(_) => return Ok(None),
+    };
+    let blob = repo
+        .find_blob(entry.id())
+        .map_err(|err| err.message().to_string())?;
+    let text =
+        std::str::from_utf8(blob.content()).map_err(|_| format!("{} is not valid UTF-8", path))?;
+
+    let mut value = Value::Null;
+    for document in serde_yaml::Deserializer::from_str(text) {
+        value = Value::deserialize(document).map_err(|err| format!("{}: {}", path, err))?;
+    }
+    value
+        .apply_merge()
+        .map_err(|err| format!("{}: {}", path, err))?;
+    Ok(Some(value))
+}
+
+/// Replaces the `include:` key of the config by the contents of the included local files.
+/// The including file takes precedence over included ones.
+fn resolve_includes(
+    repo: &Repository,
+    tree: &Tree,
+    files: &mut TreeFiles,
+    config: &mut Value,
+    depth: usize,
+) -> Result<(), String> {
+    let includes = match config
+        .as_mapping_mut()
+        .and_then(|m| m.shift_remove("include"))
+    {
+        Some(Value::Sequence(includes)) => includes,
+        Some(include) => vec![include],
+        None => return Ok(()),
+    };
+    if depth >= MAX_INCLUDE_DEPTH {
+        return Err("Too many nested includes".to_string());
+    }
+
+    let mut merged = Value::Mapping(Mapping::new());
+    for include in includes {
+        let pattern = match &include {
+            Value::String(s) if !s.contains("://") => s.as_str(),
+            Value::Mapping(m) => match m.get("local").and_then(Value::as_str) {
+                Some(local) => local,
+                None => continue,
+            },
+            _ => continue,
+        };
+        let pattern = pattern.trim_start_matches('/');
+        let paths = if pattern.contains('*') {
+            let glob = glob_to_regex(pattern);
+            files
+                .all()
+                .iter()
+                .filter(|p| glob.is_match(p) && (p.ends_with(".yml") || p.ends_with(".yaml")))
+                .cloned()
+                .collect()
+        } else {
+            vec![pattern.to_string()]
+        };
+        for path in paths {
+            let mut included = read_yaml(repo, tree, &path)?
+                .ok_or_else(|| format!("Included file {} does not exist", path))?;
+            resolve_includes(repo, tree, files, &mut included, depth + 1)?;
+            deep_merge(&mut merged, included);
+        }
+    }
+    deep_merge(&mut merged, std::mem::take(config));
+    *config = merged;
+    Ok(())
+}
+
+fn deep_merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) if existing.is_mapping() && value.is_mapping() => {
+                        deep_merge(existing, value)
+                    }
+                    _ => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// The job's definition merged with the (recursively resolved) jobs it `extends`.
+fn resolve_extends(config: &Mapping, name: &str, depth: usize) -> Result<Mapping, String> {
+    if depth > MAX_EXTENDS_DEPTH {
+        return Err(format!("{}: extends nested too deeply", name));
+    }
+    let mut job = match config.get(name) {
+        Some(Value::Mapping(job)) => job.clone(),
+        _ => return Err(format!("{}: unknown job to extend", name)),
+    };
+    let parents = match job.remove("extends") {
+        Some(Value::String(parent)) => vec![parent],
+        Some(Value::Sequence(parents)) => parents
+            .into_iter()
+            .filter_map(|p| p.as_str().map(str::to_string))
+            .collect(),
+        _ => return Ok(job),
+    };
+    let mut resolved = Value::Mapping(Mapping::new());
+    for parent in parents {
+        deep_merge(
+            &mut resolved,
+            Value::Mapping(resolve_extends(config, &parent, depth + 1)?),
+        );
+    }
+    deep_merge(&mut resolved, Value::Mapping(job));
+    match resolved {
+        Value::Mapping(resolved) => Ok(resolved),
+        _ => unreachable!(),
+    }
+}
+
+/// Job names for `parallel: N` (`name 1/N`) and `parallel: matrix` (`name: [a, b]`).
+fn parallel_names(name: &str, parallel: Option<&Value>) -> Vec<String> {
+    match parallel {
+        Some(Value::Number(n)) => {
+            let n = n.as_u64().unwrap_or(1).max(1);
+            (1..=n).map(|i| format!("{} {}/{}", name, i, n)).collect()
+        }
+        Some(Value::Mapping(m)) => {
+            let entries = match m.get("matrix") {
+                Some(Value::Sequence(entries)) => entries,
+                _ => return vec![name.to_string()],
+            };
+            let mut names = vec![];
+            for entry in entries.iter().filter_map(Value::as_mapping) {
+                let mut combinations: Vec<Vec<String>> = vec![vec![]];
+                for values in entry.values() {
+                    let values: Vec<String> = match values {
+                        Value::Sequence(values) => values.iter().map(scalar_string).collect(),
+                        value => vec![scalar_string(value)],
+                    };
+                    combinations = combinations
+                        .into_iter()
+                        .flat_map(|combination| {
+                            values.iter().map(move |v| {
+                                let mut combination = combination.clone();
+                                combination.push(v.clone());
+                                combination
+                            })
+                        })
+                        .collect();
+                }
+                names.extend(
+                    combinations
+                        .into_iter()
+                        .map(|c| format!("{}: [{}]", name, c.join(", "))),
+                );
+            }
+            if names.is_empty() {
+                vec![name.to_string()]
+            } else {
+                names
+            }
+        }
+        _ => vec![name.to_string()],
+    }
+}
+
+fn scalar_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Lazily listed paths of all files in a tree.
+struct TreeFiles<'a> {
+    tree: &'a Tree<'a>,
+    files: Option<Vec<String>>,
+}
+
+impl<'a> TreeFiles<'a> {
+    fn new(tree: &'a Tree<'a>) -> Self {
+        Self { tree, files: None }
+    }
+
+    fn all(&mut self) -> &[String] {
+        let tree = self.tree;
+        self.files.get_or_insert_with(|| {
+            let mut files = vec![];
+            let _ = tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+                if entry.kind() == Some(ObjectType::Blob) {
+                    if let Some(name) = entry.name() {
+                        files.push(format!("{}{}", dir, name));
+                    }
+                }
+                TreeWalkResult::Ok
+            });
+            files
+        })
+    }
+}
+
+/// Converts a GitLab glob (`**`, `*`, `?`, `{a,b}`) to an anchored regex.
+fn glob_to_regex(glob: &str) -> Regex {
+    let mut re = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(?:.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            '{' => re.push_str("(?:"),
+            '}' => re.push(')'),
+            ',' => re.push('|'),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    Regex::new(&re).unwrap_or_else(|_| Regex::new("^$").unwrap())
+}
+
+/// Evaluates `rules`, `only`/`except` and `when` for a push pipeline.
+struct Evaluator<'a, 't> {
+    files: &'a mut TreeFiles<'t>,
+    changed: Option<HashSet<String>>,
+    variables: HashMap<String, String>,
+    branch: Option<String>,
+}
+
+impl<'a, 't> Evaluator<'a, 't> {
+    fn new(
+        repo: &Repository,
+        commit: &Commit,
+        files: &'a mut TreeFiles<'t>,
+        target: &PreviewRef,
+        config: &Mapping,
+    ) -> Self {
+        let mut variables = HashMap::new();
+        let sha = commit.id().to_string();
+        variables.insert("CI_COMMIT_SHORT_SHA".to_string(), sha[..8].to_string());
+        variables.insert("CI_COMMIT_SHA".to_string(), sha);
+        variables.insert("CI_PIPELINE_SOURCE".to_string(), "push".to_string());
+        variables.insert("CI".to_string(), "true".to_string());
+        variables.insert("GITLAB_CI".to_string(), "true".to_string());
+        if let Some(message) = commit.message() {
+            variables.insert("CI_COMMIT_MESSAGE".to_string(), message.to_string());
+            let title = message.lines().next().unwrap_or("").to_string();
+            variables.insert("CI_COMMIT_TITLE".to_string(), title);
+        }
+        if let Some(branch) = &target.branch {
+            variables.insert("CI_COMMIT_BRANCH".to_string(), branch.clone());
+            variables.insert("CI_COMMIT_REF_NAME".to_string(), branch.clone());
+        }
+        if let Some(default_branch) = &target.default_branch {
+            variables.insert("CI_DEFAULT_BRANCH".to_string(), default_branch.clone());
+        }
+        add_variables(&mut variables, config.get("variables"));
+
+        // Changes are compared to the first parent; root commits change everything
+        let changed = commit.parent(0).ok().and_then(|parent| {
+            let diff = repo
+                .diff_tree_to_tree(
+                    parent.tree().ok().as_ref(),
+                    commit.tree().ok().as_ref(),
+                    None,
+                )
+                .ok()?;
+            Some(
+                diff.deltas()
+                    .flat_map(|d| [d.old_file().path(), d.new_file().path()])
+                    .flatten()
+                    .filter_map(|p| p.to_str().map(str::to_string))
+                    .collect(),
+            )
+        });
+
+        Self {
+            files,
+            changed,
+            variables,
+            branch: target.branch.clone(),
+        }
+    }
+
+    /// Status and `allow_failure` of the job, or `None` if it would not be created.
+    fn job(&mut self, job: &Mapping) -> Option<(PipelineStatus, bool)> {
+        let job_when = job
+            .get("when")
+            .and_then(Value::as_str)
+            .unwrap_or("on_success")
+            .to_string();
+        let job_allow_failure = job.get("allow_failure").map(allow_failure);
+
+        let (when, allow_failure) = if let Some(rules) = job.get("rules") {
+            let (when, rule_allow_failure) = self.rules(rules, job)?;
+            let when = if when.is_empty() { job_when } else { when };
+            let allow_failure = rule_allow_failure.or(job_allow_failure).unwrap_or(false);
+            (when, allow_failure)
+        } else {
+            let only = job
+                .get("only")
+                .cloned()
+                .unwrap_or_else(|| Value::Sequence(vec!["branches".into(), "tags".into()]));
+            if !self.only_except(&only, job) {
+                return None;
+            }
+            if let Some(except) = job.get("except") {
+                if self.only_except(except, job) {
+                    return None;
+                }
+            }
+            // Manual jobs outside of rules may fail by default
+            let allow_failure = job_allow_failure.unwrap_or(job_when == "manual");
+            (job_when, allow_failure)
+        };
+
+        let status = match when.as_str() {
+            "never" => return None,
+            "manual" => PipelineStatus::Manual,
+            "delayed" => PipelineStatus::Scheduled,
+            _ => PipelineStatus::Created,
+        };
+        Some((status, allow_failure))
+    }
+
+    /// The `when` (empty if not given) and `allow_failure` of the first matching rule.
+    fn rules(&mut self, rules: &Value, job: &Mapping) -> Option<(String, Option<bool>)> {
+        let rules = rules.as_sequence()?;
+        for rule in rules.iter().filter_map(Value::as_mapping) {
+            if let Some(condition) = rule.get("if").and_then(Value::as_str) {
+                let mut variables = self.variables.clone();
+                add_variables(&mut variables, job.get("variables"));
+                if evaluate(condition, &variables) == Some(false) {
+                    continue;
+                }
+            }
+            if let Some(changes) = rule.get("changes") {
+                if !self.changes(changes) {
+                    continue;
+                }
+            }
+            if let Some(exists) = rule.get("exists") {
+                if !self.exists(exists) {
+                    continue;
+                }
+            }
+            let when = rule
+                .get("when")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            return Some((when, rule.get("allow_failure").map(allow_failure)));
+        }
+        None
+    }
+
+    /// Whether all keys of an `only` or `except` specification match.
+    fn only_except(&mut self, spec: &Value, job: &Mapping) -> bool {
+        match spec {
+            Value::Sequence(refs) => self.refs(refs),
+            Value::String(r) => self.refs(&[Value::String(r.clone())]),
+            Value::Mapping(spec) => {
+                let mut matches = true;
+                if let Some(refs) = spec.get("refs").and_then(Value::as_sequence) {
+                    matches &= self.refs(refs);
+                }
+                if let Some(expressions) = spec.get("variables").and_then(Value::as_sequence) {
+                    let mut variables = self.variables.clone();
+                    add_variables(&mut variables, job.get("variables"));
+                    matches &= expressions
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .any(|e| evaluate(e, &variables) != Some(false));
+                }
+                if let Some(changes) = spec.get("changes") {
+                    matches &= self.changes(changes);
+                }
+                matches
+            }
+            _ => true,
+        }
+    }
+
+    fn refs(&self, refs: &[Value]) -> bool {
+        refs.iter().filter_map(Value::as_str).any(|r| match r {
+            "branches" => self.branch.is_some(),
+            "pushes" => true,
+            "tags"
+            | "api"
+            | "chat"
+            | "external"
+            | "external_pull_requests"
+            | "merge_requests"
+            | "pipelines"
+            | "schedules"
+            | "triggers"
+            | "web" => false,
+            r => {
+                let branch = match &self.branch {
+                    Some(branch) => branch,
+                    None => return false,
+                };
+                let r = r.split('@').next().unwrap_or(r);
+                match parse_regex(r) {
+                    Some(re) => re.is_match(branch),
+                    None => r == branch,
+                }
+            }
+        })
+    }
+
+    fn changes(&self, changes: &Value) -> bool {
+        let changed = match &self.changed {
+            Some(changed) => changed,
+            None => return true,
+        };
+        let globs: Vec<Regex> = glob_list(changes).map(glob_to_regex).collect();
+        changed
+            .iter()
+            .any(|file| globs.iter().any(|g| g.is_match(file)))
+    }
+
+    fn exists(&mut self, exists: &Value) -> bool {
+        let globs: Vec<Regex> = glob_list(exists).map(glob_to_regex).collect();
+        self.files
+            .all()
+            .iter()
+            .any(|file| globs.iter().any(|g| g.is_match(file)))
+    }
+}
+
+/// Patterns of `changes:`/`exists:`, either a list or `{ paths: [...] }`.
+fn glob_list(value: &Value) -> impl Iterator<Item = &str> {
+    let list = match value {
+        Value::Mapping(m) => m.get("paths").and_then(Value::as_sequence),
+        value => value.as_sequence(),
+    };
+    list.into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|p| p.trim_start_matches('/'))
+}
+
+fn allow_failure(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        // `allow_failure: { exit_codes: ... }`
+        Value::Mapping(_) => true,
+        _ => false,
+    }
+}
+
+fn add_variables(variables: &mut HashMap<String, String>, defined: Option<&Value>) {
+    let defined = match defined.and_then(Value::as_mapping) {
+        Some(defined) => defined,
+        None => return,
+    };
+    for (name, value) in defined {
+        let value = match value {
+            Value::Mapping(m) => m.get("value").map(scalar_string).unwrap_or_default(),
+            value => scalar_string(value),
+        };
+        if let Some(name) = name.as_str() {
+            variables.insert(name.to_string(), value);
+        }
+    }
+}
+
+/// A `/pattern/flags` regex literal.
+fn parse_regex(literal: &str) -> Option<Regex> {
+    let body = literal.strip_prefix('/')?;
+    let end = body.rfind('/')?;
+    let flags = &body[end + 1..];
+    RegexBuilder::new(&body[..end])
+        .case_insensitive(flags.contains('i'))
+        .multi_line(flags.contains('m'))
+        .build()
+        .ok()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Variable(String),
+    Str(String),
+    Regex(String),
+    Null,
+    Eq,
+    Ne,
+    Match,
+    NoMatch,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+/// Evaluates a `rules:if` expression. `None` if the expression is not understood.
+fn evaluate(expression: &str, variables: &HashMap<String, String>) -> Option<bool> {
+    let tokens = tokenize(expression)?;
+    let mut parser = ExprParser {
+        tokens: &tokens,
+        pos: 0,
+        variables,
+    };
+    let result = parser.or()?;
+    if parser.pos == tokens.len() {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+fn tokenize(expression: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            ' ' | '\t' | '\n' => i += 1,
+            '(' => {
+                tokens.push(Token::Open);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                i += 1;
+            }
+            '=' | '!' if next == Some('=') => {
+                tokens.push(if c == '=' { Token::Eq } else { Token::Ne });
+                i += 2;
+            }
+            '=' | '!' if next == Some('~') => {
+                tokens.push(if c == '=' {
+                    Token::Match
+                } else {
+                    Token::NoMatch
+                });
+                i += 2;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            '$' => {
+                let braced = next == Some('{');
+                let start = if braced { i + 2 } else { i + 1 };
+                let mut end = start;
+                while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+                    end += 1;
+                }
+                tokens.push(Token::Variable(chars[start..end].iter().collect()));
+                i = if braced { end + 1 } else { end };
+            }
+            '"' | '\'' => {
+                let end = (i + 1..chars.len()).find(|&j| chars[j] == c)?;
+                tokens.push(Token::Str(chars[i + 1..end].iter().collect()));
+                i = end + 1;
+            }
+            '/' => {
+                let mut end = i + 1;
+                while end < chars.len() && chars[end] != '/' {
+                    if chars[end] == '\\' {
+                        end += 1;
+                    }
+                    end += 1;
+                }
+                if end >= chars.len() {
+                    return None;
+                }
+                end += 1;
+                while end < chars.len() && chars[end].is_ascii_alphabetic() {
+                    end += 1;
+                }
+                tokens.push(Token::Regex(chars[i..end].iter().collect()));
+                i = end;
+            }
+            _ if chars[i..].starts_with(&['n', 'u', 'l', 'l']) => {
+                tokens.push(Token::Null);
+                i += 4;
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+#[derive(Debug)]
+enum Operand {
+    Value(Option<String>),
+    Regex(String),
+}
+
+struct ExprParser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    variables: &'a HashMap<String, String>,
+}
+
+impl ExprParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn or(&mut self) -> Option<bool> {
+        let mut result = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            result |= self.and()?;
+        }
+        Some(result)
+    }
+
+    fn and(&mut self) -> Option<bool> {
+        let mut result = self.comparison()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            result &= self.comparison()?;
+        }
+        Some(result)
+    }
+
+    fn comparison(&mut self) -> Option<bool> {
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let result = self.or()?;
+            if self.peek() != Some(&Token::Close) {
+                return None;
+            }
+            self.pos += 1;
+            return Some(result);
+        }
+        let left = self.operand()?;
+        let op = match self.peek() {
+            Some(op @ (Token::Eq | Token::Ne | Token::Match | Token::NoMatch)) => op.clone(),
+            _ => {
+                return Some(match left {
+                    Operand::Value(value) => value.is_some_and(|v| !v.is_empty()),
+                    Operand::Regex(_) => true,
+                })
+            }
+        };
+        self.pos += 1;
+        let right = self.operand()?;
+        let value = match left {
+            Operand::Value(value) => value,
+            Operand::Regex(_) => return None,
+        };
+        match op {
+            Token::Eq | Token::Ne => {
+                let other = match right {
+                    Operand::Value(other) => other,
+                    Operand::Regex(_) => return None,
+                };
+                Some((value == other) == (op == Token::Eq))
+            }
+            _ => {
+                let pattern = match right {
+                    Operand::Regex(pattern) => pattern,
+                    // The pattern may be stored in a variable
+                    Operand::Value(Some(pattern)) => pattern,
+                    Operand::Value(None) => return Some(op == Token::NoMatch),
+                };
+                let re = parse_regex(&pattern)?;
+                let matches = value.is_some_and(|v| re.is_match(&v));
+                Some(matches == (op == Token::Match))
+            }
+        }
+    }
+
+    fn operand(&mut self) -> Option<Operand> {
+        let token = self.peek()?.clone();
+        self.pos += 1;
+        match token {
+            Token::Variable(name) => Some(Operand::Value(self.variables.get(&name).cloned())),
+            Token::Str(s) => Some(Operand::Value(Some(s))),
+            Token::Regex(r) => Some(Operand::Regex(r)),
+            Token::Null => Some(Operand::Value(None)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    /// Commits the files on top of the current HEAD of the repository.
+    fn commit(repo: &Repository, files: &[(&str, &str)]) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            let full_path = workdir.join(path);
+            std::fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+            std::fs::write(full_path, content).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&Commit> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "test",
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    fn preview(
+        files: &[(&str, &str)],
+        branch: &str,
+    ) -> Vec<(String, String, PipelineStatus, bool)> {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit(&repo, &[("README.md", "readme")]);
+        let oid = commit(&repo, files);
+        let target = PreviewRef {
+            branch: Some(branch.to_string()),
+            default_branch: Some("main".to_string()),
+        };
+        preview_pipeline(&repo, oid, &target)
+            .unwrap()
+            .unwrap()
+            .stages
+            .into_iter()
+            .flat_map(|stage| stage.jobs)
+            .map(|job| {
+                let allow_failure = job.allow_failure.unwrap();
+                (job.stage, job.name, job.status, allow_failure)
+            })
+            .collect()
+    }
+
+    fn names(jobs: &[(String, String, PipelineStatus, bool)]) -> Vec<&str> {
+        jobs.iter().map(|(_, name, _, _)| name.as_str()).collect()
+    }
+
+    fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_expressions() {
+        assert_eq!(
+            tokenize(r#"$A == "x" && (${B} =~ /^v\/\d/i || null != $C)"#),
+            Some(vec![
+                Token::Variable("A".to_string()),
+                Token::Eq,
+                Token::Str("x".to_string()),
+                Token::And,
+                Token::Open,
+                Token::Variable("B".to_string()),
+                Token::Match,
+                Token::Regex(r"/^v\/\d/i".to_string()),
+                Token::Or,
+                Token::Null,
+                Token::Ne,
+                Token::Variable("C".to_string()),
+                Token::Close,
+            ])
+        );
+        assert_eq!(tokenize("$A == 'unterminated"), None);
+        assert_eq!(tokenize("$A =~ /unterminated"), None);
+        assert_eq!(tokenize("$A < 3"), None);
+    }
+
+    #[test]
+    fn evaluates_expressions() {
+        let vars = variables(&[
+            ("CI_COMMIT_BRANCH", "release/1.2"),
+            ("EMPTY", ""),
+            ("PATTERN", "/^release/"),
+        ]);
+        let eval = |expression| evaluate(expression, &vars);
+        assert_eq!(eval("$CI_COMMIT_BRANCH"), Some(true));
+        assert_eq!(eval("$EMPTY"), Some(false));
+        assert_eq!(eval("$UNDEFINED"), Some(false));
+        assert_eq!(eval("$UNDEFINED == null"), Some(true));
+        assert_eq!(eval("$CI_COMMIT_BRANCH == 'main'"), Some(false));
+        assert_eq!(eval("$CI_COMMIT_BRANCH != \"main\""), Some(true));
+        assert_eq!(eval("$CI_COMMIT_BRANCH =~ /^RELEASE\\//i"), Some(true));
+        assert_eq!(eval("$CI_COMMIT_BRANCH !~ /^release/"), Some(false));
+        assert_eq!(eval("$CI_COMMIT_BRANCH =~ $PATTERN"), Some(true));
+        assert_eq!(eval("$UNDEFINED =~ /x/"), Some(false));
+        assert_eq!(
+            eval("$EMPTY || $CI_COMMIT_BRANCH && $UNDEFINED"),
+            Some(false)
+        );
+        assert_eq!(
+            eval("($EMPTY || $CI_COMMIT_BRANCH) && $PATTERN"),
+            Some(true)
+        );
+        assert_eq!(eval("($CI_COMMIT_BRANCH"), None);
+        assert_eq!(eval("$A == $B $C"), None);
+    }
+
+    #[test]
+    fn converts_globs() {
+        let glob = glob_to_regex("**/*.rs");
+        assert!(glob.is_match("main.rs"));
+        assert!(glob.is_match("src/gitlab/mod.rs"));
+        assert!(!glob.is_match("main.rsx"));
+
+        let glob = glob_to_regex("ci/*.{yml,yaml}");
+        assert!(glob.is_match("ci/build.yml"));
+        assert!(glob.is_match("ci/test.yaml"));
+        assert!(!glob.is_match("ci/jobs/build.yml"));
+        assert!(!glob.is_match("ci/build.json"));
+
+        let glob = glob_to_regex("docs/**");
+        assert!(glob.is_match("docs/a/b.md"));
+        assert!(!glob.is_match("src/docs.md"));
+
+        let glob = glob_to_regex("v?.txt");
+        assert!(glob.is_match("v1.txt"));
+        assert!(!glob.is_match("v10.txt"));
+        assert!(!glob.is_match("v/.txt"));
+    }
+
+    #[test]
+    fn names_parallel_jobs() {
+        assert_eq!(parallel_names("test", None), vec!["test"]);
+        let parallel: Value = serde_yaml::from_str("3").unwrap();
+        assert_eq!(
+            parallel_names("test", Some(&parallel)),
+            vec!["test 1/3", "test 2/3", "test 3/3"]
+        );
+        let parallel: Value = serde_yaml::from_str(
+            "matrix:\n  - OS: [linux, mac]\n    VERSION: [1, 2]\n  - OS: windows\n",
+        )
+        .unwrap();
+        assert_eq!(
+            parallel_names("test", Some(&parallel)),
+            vec![
+                "test: [linux, 1]",
+                "test: [linux, 2]",
+                "test: [mac, 1]",
+                "test: [mac, 2]",
+                "test: [windows]",
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluates_rules() {
+        let config = "
+stages: [build, test]
+build:
+  stage: build
+  script: make
+default-only:
+  script: make
+  rules:
+    - if: $CI_COMMIT_BRANCH == $CI_DEFAULT_BRANCH
+feature-manual:
+  script: make
+  rules:
+    - if: $CI_COMMIT_BRANCH =~ /^feature\\//
+      when: manual
+      allow_failure: true
+skipped:
+  script: make
+  rules:
+    - if: $CI_PIPELINE_SOURCE == 'merge_request_event'
+    - when: never
+docs:
+  script: make
+  rules:
+    - changes: ['docs/**/*']
+code:
+  script: make
+  rules:
+    - changes: ['src/**/*.rs']
+with-makefile:
+  script: make
+  rules:
+    - exists: [Makefile]
+job-variable:
+  variables:
+    DEPLOY: 'yes'
+  script: make
+  rules:
+    - if: $DEPLOY == 'yes'
+      when: delayed
+";
+        let files = [(".gitlab-ci.yml", config), ("src/main.rs", "fn main() {}")];
+        let jobs = preview(&files, "feature/x");
+        assert_eq!(
+            names(&jobs),
+            vec!["build", "feature-manual", "code", "job-variable"]
+        );
+        assert_eq!(jobs[0].0, "build");
+        assert_eq!(jobs[1].2, PipelineStatus::Manual);
+        assert!(jobs[1].3);
+        assert_eq!(jobs[3].2, PipelineStatus::Scheduled);
+
+        let jobs = preview(&files, "main");
+        assert_eq!(
+            names(&jobs),
+            vec!["build", "default-only", "code", "job-variable"]
+        );
+    }
+
+    #[test]
+    fn evaluates_only_and_except() {
+        let config = "
+all:
+  script: make
+main-only:
+  script: make
+  only: [main]
+release:
+  script: make
+  only:
+    refs: ['/^release-/']
+    variables: ['$CI_COMMIT_TITLE == \"test\"']
+not-main:
+  script: make
+  except: [main]
+tags-only:
+  script: make
+  only: [tags]
+manual:
+  script: make
+  when: manual
+";
+        let files = [(".gitlab-ci.yml", config)];
+        let jobs = preview(&files, "main");
+        assert_eq!(names(&jobs), vec!["all", "main-only", "manual"]);
+        // Manual jobs outside of rules may fail by default
+        assert!(jobs[2].3);
+        assert!(!jobs[0].3);
+
+        let jobs = preview(&files, "release-1");
+        assert_eq!(names(&jobs), vec!["all", "release", "not-main", "manual"]);
+    }
+
+    #[test]
+    fn evaluates_workflow_rules() {
+        let config = "
+workflow:
+  rules:
+    - if: $CI_COMMIT_BRANCH == 'main'
+    - when: never
+build:
+  script: make
+";
+        let files = [(".gitlab-ci.yml", config)];
+        assert_eq!(names(&preview(&files, "main")), vec!["build"]);
+        assert!(preview(&files, "topic").is_empty());
+    }
+
+    #[test]
+    fn resolves_extends_and_local_includes() {
+        let config = "
+include:
+  - local: /ci/base.yml
+  - 'ci/jobs/*.yml'
+  - remote: https://example.com/ci.yml
+stages: [build, test, deploy]
+.deploy:
+  extends: .base
+  stage: deploy
+  when: manual
+deploy:
+  extends: [.deploy, .tagged]
+  allow_failure: false
+";
+        let base = "
+.base:
+  stage: build
+  script: make
+.tagged:
+  tags: [docker]
+";
+        let jobs = "
+lint:
+  extends: .base
+  stage: test
+  parallel: 2
+";
+        let files = [
+            (".gitlab-ci.yml", config),
+            ("ci/base.yml", base),
+            ("ci/jobs/lint.yml", jobs),
+        ];
+        let jobs = preview(&files, "main");
+        assert_eq!(names(&jobs), vec!["lint 1/2", "lint 2/2", "deploy"]);
+        assert_eq!(jobs[0].0, "test");
+        assert_eq!(jobs[2].0, "deploy");
+        assert_eq!(jobs[2].2, PipelineStatus::Manual);
+        assert!(!jobs[2].3);
+    }
+
+    #[test]
+    fn reports_missing_includes_and_unknown_extends() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let target = PreviewRef::default();
+
+        let oid = commit(&repo, &[(".gitlab-ci.yml", "include: missing.yml\n")]);
+        let err = preview_pipeline(&repo, oid, &target).unwrap_err();
+        assert!(err.contains("missing.yml"), "{}", err);
+
+        let oid = commit(&repo, &[(".gitlab-ci.yml", "job:\n  extends: .nope\n")]);
+        let err = preview_pipeline(&repo, oid, &target).unwrap_err();
+        assert!(err.contains(".nope"), "{}", err);
+
+        std::fs::remove_file(dir.path().join(CI_CONFIG_PATH)).unwrap();
+        let mut index = repo.index().unwrap();
+        index.remove_path(Path::new(CI_CONFIG_PATH)).unwrap();
+        index.write().unwrap();
+        let oid = commit(&repo, &[("other.txt", "")]);
+        assert!(preview_pipeline(&repo, oid, &target).unwrap().is_none());
+    }
+}
@@ -0,0 +1,227 @@
+use super::models::{Job, PipelineDetails, PipelineStatus};
+
+/// A job is considered significantly slower if it takes this factor longer...
+const SLOWDOWN_FACTOR: f64 = 1.5;
+/// ...and at least this many seconds more.
+const SLOWDOWN_MIN_SECS: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobChange {
+    /// Failed in the newer pipeline, but not in the older one.
+    NewFailure,
+    /// Failed in the older pipeline and succeeded in the newer one.
+    Fixed,
+    Slower,
+    Added,
+    Removed,
+    Unchanged,
+}
+
+impl JobChange {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::NewFailure => "new failure",
+            Self::Fixed => "fixed",
+            Self::Slower => "slower",
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Unchanged => "",
+        }
+    }
+}
+
+/// One job name with its run in the older (`base`) and newer (`head`) pipeline.
+#[derive(Debug, Clone)]
+pub struct JobComparison {
+    pub name: String,
+    pub stage: String,
+    pub base: Option<Job>,
+    pub head: Option<Job>,
+    pub change: JobChange,
+}
+
+/// Matches the jobs of two pipelines by name, in stage order of the newer pipeline
+/// followed by jobs that only exist in the older one.
+pub fn compare_pipelines(base: &PipelineDetails, head: &PipelineDetails) -> Vec<JobComparison> {
+    let base_jobs: Vec<&Job> = base.stages.iter().flat_map(|s| &s.jobs).collect();
+    let head_jobs: Vec<&Job> = head.stages.iter().flat_map(|s| &s.jobs).collect();
+
+    let mut jobs: Vec<JobComparison> = head_jobs
+        .iter()
+        .map(|job| {
+            let old = base_jobs.iter().find(|j| j.name == job.name).copied();
+            JobComparison {
+                name: job.name.clone(),
+                stage: job.stage.clone(),
+                change: change(old, Some(job)),
+                base: old.cloned(),
+                head: Some((*job).clone()),
+            }
+        })
+        .collect();
+    jobs.extend(
+        base_jobs
+            .iter()
+            .filter(|job| !head_jobs.iter().any(|j| j.name == job.name))
+            .map(|job| JobComparison {
+                name: job.name.clone(),
+                stage: job.stage.clone(),
+                base: Some((*job).clone()),
+                head: None,
+                change: JobChange::Removed,
+            }),
+    );
+    jobs
+}
+
+fn change(base: Option<&Job>, head: Option<&Job>) -> JobChange {
+    let (base, head) = match (base, head) {
+        (Some(base), Some(head)) => (base, head),
+        (None, _) => return JobChange::Added,
+        (_, None) => return JobChange::Removed,
+    };
+    let failed =
+        |job: &Job| job.status == PipelineStatus::Failed && !job.allow_failure.unwrap_or(false);
+    if failed(head) && !failed(base) {
+        return JobChange::NewFailure;
+    }
+    if failed(base) && head.status == PipelineStatus::Success {
+        return JobChange::Fixed;
+    }
+    match (base.duration, head.duration) {
+        (Some(old), Some(new)) if new > old * SLOWDOWN_FACTOR && new - old >= SLOWDOWN_MIN_SECS => {
+            JobChange::Slower
+        }
+        _ => JobChange::Unchanged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gitlab::models::Stage;
+
+    fn job(name: &str, status: &str, duration: f64, allow_failure: bool) -> Job {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": name,
+            "status": status,
+            "stage": "test",
+            "duration": duration,
+            "allow_failure": allow_failure,
+        }))
+        .unwrap()
+    }
+
+    fn pipeline(jobs: Vec<Job>) -> PipelineDetails {
+        let mut stage = Stage::new("test".to_string());
+        stage.jobs = jobs;
+        PipelineDetails {
+            pipeline: None,
+            stages: vec![stage],
+            preview: false,
+            pipelines: vec![],
+            with_bridges: true,
+        }
+    }
+
+    fn compare(base: Job, head: Job) -> JobChange {
+        change(Some(&base), Some(&head))
+    }
+
+    #[test]
+    fn detects_new_failures_unless_allowed_to_fail() {
+        let success = job("test", "success", 60.0, false);
+        assert_eq!(
+            compare(success.clone(), job("test", "failed", 60.0, false)),
+            JobChange::NewFailure
+        );
+        assert_eq!(
+            compare(success, job("test", "failed", 60.0, true)),
+            JobChange::Unchanged
+        );
+        assert_eq!(
+            compare(
+                job("test", "failed", 60.0, true),
+                job("test", "failed", 60.0, false)
+            ),
+            JobChange::NewFailure
+        );
+        assert_eq!(
+            compare(
+                job("test", "failed", 60.0, false),
+                job("test", "failed", 60.0, false)
+            ),
+            JobChange::Unchanged
+        );
+    }
+
+    #[test]
+    fn detects_fixed_jobs() {
+        assert_eq!(
+            compare(
+                job("test", "failed", 60.0, false),
+                job("test", "success", 60.0, false)
+            ),
+            JobChange::Fixed
+        );
+        // Still running, not fixed yet
+        assert_eq!(
+            compare(
+                job("test", "failed", 60.0, false),
+                job("test", "running", 60.0, false)
+            ),
+            JobChange::Unchanged
+        );
+    }
+
+    #[test]
+    fn detects_slower_jobs_by_factor_and_minimum() {
+        let base = job("test", "success", 60.0, false);
+        assert_eq!(
+            compare(base.clone(), job("test", "success", 91.0, false)),
+            JobChange::Slower
+        );
+        // Not 1.5 times as long
+        assert_eq!(
+            compare(base, job("test", "success", 89.0, false)),
+            JobChange::Unchanged
+        );
+        // 3 times as long, but less than 30 seconds more
+        assert_eq!(
+            compare(
+                job("test", "success", 10.0, false),
+                job("test", "success", 30.0, false)
+            ),
+            JobChange::Unchanged
+        );
+    }
+
+    #[test]
+    fn matches_jobs_by_name() {
+        let base = pipeline(vec![
+            job("build", "success", 60.0, false),
+            job("lint", "failed", 60.0, false),
+            job("old", "success", 60.0, false),
+        ]);
+        let head = pipeline(vec![
+            job("build", "failed", 60.0, false),
+            job("lint", "success", 60.0, false),
+            job("new", "success", 60.0, false),
+        ]);
+        let comparison = compare_pipelines(&base, &head);
+        let jobs: Vec<(&str, JobChange)> = comparison
+            .iter()
+            .map(|job| (job.name.as_str(), job.change))
+            .collect();
+        assert_eq!(
+            jobs,
+            vec![
+                ("build", JobChange::NewFailure),
+                ("lint", JobChange::Fixed),
+                ("new", JobChange::Added),
+                ("old", JobChange::Removed),
+            ]
+        );
+    }
+}
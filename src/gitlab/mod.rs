@@ -1,8 +1,24 @@
+pub mod analytics;
+pub mod ci_config;
+pub mod compare;
 pub mod models;
+pub mod problems;
 
-use models::{Job, Pipeline, PipelineDetails};
+use analytics::JobAnalytics;
+use models::{
+    Job, LintResult, MergeRequest, Pipeline, PipelineDetails, PipelinePolicy, PipelineVariable,
+};
 use reqwest::blocking::Client;
 use reqwest::header::{HeaderMap, HeaderValue, ACCEPT};
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+
+/// Largest page size the GitLab API allows.
+const PER_PAGE: usize = 100;
+/// Stops paginated requests early for runaway lists.
+const MAX_PAGES: usize = 50;
+/// Requests run at the same time when fetching one item per entry of a list.
+const PARALLEL_REQUESTS: usize = 8;
 
 pub struct GitLabClient {
     client: Client,
@@ -41,12 +57,34 @@ impl GitLabClient {
         project_id: &str,
         sha: &str,
     ) -> Result<Option<Pipeline>, String> {
+        Ok(self
+            .get_pipelines_for_commit(project_id, sha)?
+            .into_iter()
+            .next())
+    }
+
+    /// Fetches all pipelines of a commit, newest first.
+    pub fn get_pipelines_for_commit(
+        &self,
+        project_id: &str,
+        sha: &str,
+    ) -> Result<Vec<Pipeline>, String> {
         let url = format!(
             "{}/api/v4/projects/{}/pipelines?sha={}",
             self.base_url,
             urlencoded(project_id),
             sha
         );
+        self.get_paginated(&url, None, "pipelines")
+    }
+
+    pub fn get_pipeline(&self, project_id: &str, pipeline_id: u64) -> Result<Pipeline, String> {
+        let url = format!(
+            "{}/api/v4/projects/{}/pipelines/{}",
+            self.base_url,
+            urlencoded(project_id),
+            pipeline_id
+        );
 
         let response = self
             .client
@@ -59,24 +97,143 @@ impl GitLabClient {
             return Err(format!("GitLab API error: {}", response.status()));
         }
 
-        let pipelines: Vec<Pipeline> = response
+        response
             .json()
-            .map_err(|e| format!("Failed to parse pipelines: {}", e))?;
+            .map_err(|e| format!("Failed to parse pipeline: {}", e))
+    }
+
+    /// Fetches the variables a pipeline was created with.
+    pub fn get_pipeline_variables(
+        &self,
+        project_id: &str,
+        pipeline_id: u64,
+    ) -> Result<Vec<PipelineVariable>, String> {
+        let url = format!(
+            "{}/api/v4/projects/{}/pipelines/{}/variables",
+            self.base_url,
+            urlencoded(project_id),
+            pipeline_id
+        );
 
-        Ok(pipelines.into_iter().next())
+        let response = self
+            .client
+            .get(&url)
+            .headers(self.headers())
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("GitLab API error: {}", response.status()));
+        }
+
+        response
+            .json()
+            .map_err(|e| format!("Failed to parse variables: {}", e))
+    }
+
+    /// Lists the most recent pipelines of a branch or tag, newest first. Duration and
+    /// user are not included.
+    fn list_ref_pipelines(
+        &self,
+        project_id: &str,
+        ref_name: &str,
+        limit: usize,
+    ) -> Result<Vec<Pipeline>, String> {
+        let url = format!(
+            "{}/api/v4/projects/{}/pipelines?ref={}",
+            self.base_url,
+            urlencoded(project_id),
+            url::form_urlencoded::byte_serialize(ref_name.as_bytes()).collect::<String>(),
+        );
+        self.get_paginated(&url, Some(limit), "pipelines")
+    }
+
+    /// Fetches the most recent pipelines of a branch or tag, newest first,
+    /// including duration and triggering user.
+    pub fn get_ref_pipelines(
+        &self,
+        project_id: &str,
+        ref_name: &str,
+        limit: usize,
+    ) -> Result<Vec<Pipeline>, String> {
+        let pipelines = self.list_ref_pipelines(project_id, ref_name, limit)?;
+        // The list endpoint omits duration and user
+        fetch_parallel(&pipelines, |p| self.get_pipeline(project_id, p.id))
+    }
+
+    /// Aggregates job durations and outcomes over the most recent pipelines of a ref.
+    pub fn get_ref_job_analytics(
+        &self,
+        project_id: &str,
+        ref_name: &str,
+        limit: usize,
+    ) -> Result<JobAnalytics, String> {
+        let pipelines = self.list_ref_pipelines(project_id, ref_name, limit)?;
+        let runs = fetch_parallel(&pipelines, |p| {
+            let jobs = self.get_pipeline_jobs(project_id, p.id, true)?;
+            Ok((p.clone(), jobs))
+        })?;
+
+        Ok(JobAnalytics::from_runs(&runs))
     }
 
     pub fn get_pipeline_jobs(
         &self,
         project_id: &str,
         pipeline_id: u64,
+        include_retried: bool,
     ) -> Result<Vec<Job>, String> {
         let url = format!(
-            "{}/api/v4/projects/{}/pipelines/{}/jobs?per_page=100",
+            "{}/api/v4/projects/{}/pipelines/{}/jobs?include_retried={}",
+            self.base_url,
+            urlencoded(project_id),
+            pipeline_id,
+            include_retried
+        );
+        self.get_paginated(&url, None, "jobs")
+    }
+
+    /// Fetches the trigger jobs of a pipeline, which start downstream pipelines and
+    /// are not part of the job list.
+    pub fn get_pipeline_bridges(
+        &self,
+        project_id: &str,
+        pipeline_id: u64,
+    ) -> Result<Vec<Job>, String> {
+        let url = format!(
+            "{}/api/v4/projects/{}/pipelines/{}/bridges",
             self.base_url,
             urlencoded(project_id),
             pipeline_id
         );
+        let mut bridges: Vec<Job> = self.get_paginated(&url, None, "bridges")?;
+        for bridge in &mut bridges {
+            bridge.bridge = true;
+        }
+        Ok(bridges)
+    }
+
+    pub fn get_open_merge_requests(
+        &self,
+        project_id: &str,
+        source_branch: &str,
+    ) -> Result<Vec<MergeRequest>, String> {
+        let url = format!(
+            "{}/api/v4/projects/{}/merge_requests?state=opened&source_branch={}",
+            self.base_url,
+            urlencoded(project_id),
+            url::form_urlencoded::byte_serialize(source_branch.as_bytes()).collect::<String>(),
+        );
+        self.get_paginated(&url, None, "merge requests")
+    }
+
+    pub fn get_merge_request(&self, project_id: &str, iid: u64) -> Result<MergeRequest, String> {
+        let url = format!(
+            "{}/api/v4/projects/{}/merge_requests/{}",
+            self.base_url,
+            urlencoded(project_id),
+            iid
+        );
 
         let response = self
             .client
@@ -91,21 +248,178 @@ impl GitLabClient {
 
         response
             .json()
-            .map_err(|e| format!("Failed to parse jobs: {}", e))
+            .map_err(|e| format!("Failed to parse merge request: {}", e))
     }
 
+    /// Fetches the most recent pipelines of a merge request, newest first.
+    pub fn get_merge_request_pipelines(
+        &self,
+        project_id: &str,
+        iid: u64,
+    ) -> Result<Vec<Pipeline>, String> {
+        let url = format!(
+            "{}/api/v4/projects/{}/merge_requests/{}/pipelines",
+            self.base_url,
+            urlencoded(project_id),
+            iid
+        );
+        self.get_paginated(&url, Some(PER_PAGE), "pipelines")
+    }
+
+    /// Pipelines of open merge requests from the branches, by source branch tip, for the
+    /// branches' tips given by name. Each tip of a branch that could be queried has an
+    /// entry, empty if it has no merge request pipelines. Branches whose requests fail
+    /// are left out, unless all of them fail.
+    pub fn get_merge_request_pipelines_by_sha(
+        &self,
+        project_id: &str,
+        branches: &HashMap<String, HashSet<String>>,
+    ) -> Result<HashMap<String, Vec<Pipeline>>, String> {
+        let branches: Vec<(&String, &HashSet<String>)> = branches.iter().collect();
+        let results = fetch_parallel_results(&branches, |(branch, tips)| {
+            self.get_branch_merge_request_pipelines(project_id, branch, tips)
+        });
+
+        let mut by_sha: HashMap<String, Vec<Pipeline>> = HashMap::new();
+        let mut error = None;
+        for result in results {
+            match result {
+                Ok(pipelines) => {
+                    for (sha, pipelines) in pipelines {
+                        by_sha.entry(sha).or_default().extend(pipelines);
+                    }
+                }
+                Err(err) => error = Some(err),
+            }
+        }
+        match error {
+            Some(err) if by_sha.is_empty() => Err(err),
+            _ => Ok(by_sha),
+        }
+    }
+
+    /// Pipelines of the open merge requests from the branch whose tip is one of `tips`.
+    /// Merged-result pipelines run on a temporary merge commit and can't be found by the
+    /// tip's sha; the merge request's head pipeline is kept for them.
+    fn get_branch_merge_request_pipelines(
+        &self,
+        project_id: &str,
+        branch: &str,
+        tips: &HashSet<String>,
+    ) -> Result<HashMap<String, Vec<Pipeline>>, String> {
+        let mut by_sha: HashMap<String, Vec<Pipeline>> =
+            tips.iter().map(|sha| (sha.clone(), vec![])).collect();
+        for merge_request in self.get_open_merge_requests(project_id, branch)? {
+            let sha = match merge_request.sha {
+                Some(sha) if tips.contains(&sha) => sha,
+                _ => continue,
+            };
+            let head = self
+                .get_merge_request(project_id, merge_request.iid)?
+                .head_pipeline;
+            let mut pipelines: Vec<Pipeline> = self
+                .get_merge_request_pipelines(project_id, merge_request.iid)?
+                .into_iter()
+                .filter(|p| p.sha == sha || head.as_ref().is_some_and(|h| h.id == p.id))
+                .collect();
+            if let Some(head) = head {
+                if !pipelines.iter().any(|p| p.id == head.id) {
+                    pipelines.push(head);
+                }
+            }
+            by_sha.entry(sha).or_default().extend(pipelines);
+        }
+        Ok(by_sha)
+    }
+
+    /// Fetches all items of a list endpoint, following the `X-Next-Page` or `Link`
+    /// headers until `limit` items are collected or there are no more pages.
+    fn get_paginated<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        limit: Option<usize>,
+        what: &str,
+    ) -> Result<Vec<T>, String> {
+        let per_page = limit.unwrap_or(PER_PAGE).clamp(1, PER_PAGE);
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let first_url = format!("{}{}per_page={}", url, separator, per_page);
+
+        let mut items = Vec::new();
+        let mut next = Some(first_url.clone());
+        for _ in 0..MAX_PAGES {
+            let page_url = match next.take() {
+                Some(url) => url,
+                None => break,
+            };
+
+            let response = self
+                .client
+                .get(&page_url)
+                .headers(self.headers())
+                .send()
+                .map_err(|e| format!("Request failed: {}", e))?;
+
+            if !response.status().is_success() {
+                return Err(format!("GitLab API error: {}", response.status()));
+            }
+
+            next = next_page_url(response.headers(), &first_url);
+            let page: Vec<T> = response
+                .json()
+                .map_err(|e| format!("Failed to parse {}: {}", what, e))?;
+            let done = page.is_empty();
+            items.extend(page);
+            if done || limit.is_some_and(|limit| items.len() >= limit) {
+                break;
+            }
+        }
+
+        if let Some(limit) = limit {
+            items.truncate(limit);
+        }
+        Ok(items)
+    }
+
+    /// Fetches the jobs of the commit's pipeline with the given ID, or else of the
+    /// one selected by the policy, along with the list of all its pipelines. Trigger
+    /// jobs are fetched as well `with_bridges`.
+    #[allow(clippy::too_many_arguments)]
     pub fn get_pipeline_details(
         &self,
         project_id: &str,
         sha: &str,
+        policy: &PipelinePolicy,
+        pipeline_id: Option<u64>,
+        include_retried: bool,
+        with_bridges: bool,
+        merge_request_pipelines: &[u64],
     ) -> Result<Option<PipelineDetails>, String> {
-        let pipeline = match self.get_pipeline_for_commit(project_id, sha)? {
-            Some(p) => p,
+        let mut pipelines = self.get_pipelines_for_commit(project_id, sha)?;
+        if !merge_request_pipelines.is_empty() {
+            // Merged-result pipelines don't run on `sha`, fetch them by ID
+            for id in merge_request_pipelines {
+                if !pipelines.iter().any(|p| p.id == *id) {
+                    pipelines.push(self.get_pipeline(project_id, *id)?);
+                }
+            }
+            pipelines.sort_by_key(|p| std::cmp::Reverse(p.id));
+        }
+        let pipeline = match pipeline_id
+            .and_then(|id| pipelines.iter().find(|p| p.id == id))
+            .or_else(|| policy.select(&pipelines))
+        {
+            Some(p) => p.clone(),
             None => return Ok(None),
         };
 
-        let jobs = self.get_pipeline_jobs(project_id, pipeline.id)?;
-        Ok(Some(PipelineDetails::from_jobs(pipeline, jobs)))
+        let mut jobs = self.get_pipeline_jobs(project_id, pipeline.id, include_retried)?;
+        if with_bridges {
+            jobs.extend(self.get_pipeline_bridges(project_id, pipeline.id)?);
+        }
+        let mut details = PipelineDetails::from_jobs(pipeline, jobs);
+        details.pipelines = pipelines;
+        details.with_bridges = with_bridges;
+        Ok(Some(details))
     }
 
     pub fn get_job_trace(&self, project_id: &str, job_id: u64) -> Result<String, String> {
@@ -137,8 +451,187 @@ impl GitLabClient {
             .text()
             .map_err(|e| format!("Failed to read trace: {}", e))
     }
+
+    /// Validates a CI configuration, simulating pipeline creation for the ref.
+    pub fn lint_ci_config(
+        &self,
+        project_id: &str,
+        content: &str,
+        ref_name: Option<&str>,
+    ) -> Result<LintResult, String> {
+        let url = format!(
+            "{}/api/v4/projects/{}/ci/lint",
+            self.base_url,
+            urlencoded(project_id)
+        );
+
+        let mut body = serde_json::json!({
+            "content": content,
+            "dry_run": true,
+            "include_merged_yaml": true,
+        });
+        if let Some(ref_name) = ref_name {
+            body["ref"] = serde_json::Value::from(ref_name);
+        }
+
+        let response = self
+            .client
+            .post(&url)
+            .headers(self.headers())
+            .json(&body)
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("GitLab API error: {}", response.status()));
+        }
+
+        response
+            .json()
+            .map_err(|e| format!("Failed to parse lint result: {}", e))
+    }
+}
+
+/// URL of the next page from the `X-Next-Page` header of offset pagination, or the
+/// `Link` header of keyset pagination, which GitLab sends without `X-Next-Page`.
+fn next_page_url(headers: &HeaderMap, first_url: &str) -> Option<String> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    if let Some(page) = header("x-next-page").map(str::trim) {
+        return (!page.is_empty()).then(|| format!("{}&page={}", first_url, page));
+    }
+    header("link")?.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        params.contains("rel=\"next\"").then(|| {
+            target
+                .trim()
+                .trim_matches(|c| c == '<' || c == '>')
+                .to_string()
+        })
+    })
+}
+
+/// Runs `fetch` for each item, `PARALLEL_REQUESTS` at a time, keeping the order.
+/// Stops at the first error.
+fn fetch_parallel<I: Sync, T: Send>(
+    items: &[I],
+    fetch: impl Fn(&I) -> Result<T, String> + Sync,
+) -> Result<Vec<T>, String> {
+    let mut results = Vec::with_capacity(items.len());
+    for chunk in items.chunks(PARALLEL_REQUESTS) {
+        for result in fetch_chunk(chunk, &fetch) {
+            results.push(result?);
+        }
+    }
+    Ok(results)
+}
+
+/// Like `fetch_parallel`, but returns the result of every item.
+fn fetch_parallel_results<I: Sync, T: Send>(
+    items: &[I],
+    fetch: impl Fn(&I) -> Result<T, String> + Sync,
+) -> Vec<Result<T, String>> {
+    items
+        .chunks(PARALLEL_REQUESTS)
+        .flat_map(|chunk| fetch_chunk(chunk, &fetch))
+        .collect()
+}
+
+/// Runs `fetch` for all items at once.
+fn fetch_chunk<I: Sync, T: Send>(
+    chunk: &[I],
+    fetch: &(impl Fn(&I) -> Result<T, String> + Sync),
+) -> Vec<Result<T, String>> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = chunk
+            .iter()
+            .map(|item| scope.spawn(|| fetch(item)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err("Request thread panicked".to_string()))
+            })
+            .collect()
+    })
 }
 
 fn urlencoded(s: &str) -> String {
     s.replace('/', "%2F")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetch_parallel_keeps_order_and_fails_on_error() {
+        let items: Vec<u64> = (0..20).collect();
+        let doubled = fetch_parallel(&items, |i| Ok(i * 2)).unwrap();
+        assert_eq!(doubled, items.iter().map(|i| i * 2).collect::<Vec<_>>());
+
+        let result = fetch_parallel(&items, |i| {
+            if *i == 11 {
+                Err(format!("failed {}", i))
+            } else {
+                Ok(*i)
+            }
+        });
+        assert_eq!(result, Err("failed 11".to_string()));
+
+        let results = fetch_parallel_results(&items, |i| {
+            if i % 5 == 0 {
+                Err(format!("failed {}", i))
+            } else {
+                Ok(*i)
+            }
+        });
+        assert_eq!(results.len(), items.len());
+        assert_eq!(results[10], Err("failed 10".to_string()));
+        assert_eq!(results[11], Ok(11));
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn next_page_url_follows_offset_and_keyset_pagination() {
+        let first = "https://gitlab.example.com/api/v4/projects/1/jobs?per_page=100";
+
+        assert_eq!(
+            next_page_url(&headers(&[("x-next-page", "2")]), first),
+            Some(format!("{}&page=2", first))
+        );
+        // Empty on the last page
+        assert_eq!(next_page_url(&headers(&[("x-next-page", "")]), first), None);
+        assert_eq!(next_page_url(&headers(&[]), first), None);
+
+        let next = "https://gitlab.example.com/api/v4/projects/1/jobs?cursor=abc&per_page=100";
+        let link = "<https://gitlab.example.com/api/v4/projects/1/jobs?per_page=100>; rel=\"first\", \
+                    <https://gitlab.example.com/api/v4/projects/1/jobs?cursor=abc&per_page=100>; rel=\"next\"";
+        assert_eq!(
+            next_page_url(&headers(&[("link", link)]), first),
+            Some(next.to_string())
+        );
+        let link =
+            "<https://gitlab.example.com/api/v4/projects/1/jobs?per_page=100>; rel=\"first\"";
+        assert_eq!(next_page_url(&headers(&[("link", link)]), first), None);
+
+        // Offset pagination takes precedence, also on its last page
+        let link = "<https://gitlab.example.com/next>; rel=\"next\"";
+        assert_eq!(
+            next_page_url(&headers(&[("x-next-page", "3"), ("link", link)]), first),
+            Some(format!("{}&page=3", first))
+        );
+        assert_eq!(
+            next_page_url(&headers(&[("x-next-page", " "), ("link", link)]), first),
+            None
+        );
+    }
+}
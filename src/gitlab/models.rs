@@ -1,6 +1,7 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PipelineStatus {
     Created,
@@ -47,12 +48,34 @@ impl PipelineStatus {
         }
     }
 
+    /// How much attention a pipeline with the status needs, lowest first: failed,
+    /// active, canceled, waiting for a user or schedule, passed, skipped.
+    pub fn severity(&self) -> u8 {
+        match self {
+            Self::Failed => 0,
+            status if status.is_active() => 1,
+            Self::Canceled | Self::Canceling => 2,
+            Self::Manual | Self::Created | Self::Scheduled => 3,
+            Self::Success => 4,
+            _ => 5,
+        }
+    }
+
     pub fn is_active(&self) -> bool {
         matches!(
             self,
             Self::Running | Self::Pending | Self::WaitingForResource | Self::Preparing
         )
     }
+
+    /// Whether the pipeline stopped, including waiting for a manual job, which
+    /// doesn't progress on its own.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            Self::Success | Self::Failed | Self::Canceled | Self::Skipped | Self::Manual
+        )
+    }
 }
 
 impl std::fmt::Display for PipelineStatus {
@@ -75,7 +98,7 @@ impl std::fmt::Display for PipelineStatus {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pipeline {
     pub id: u64,
     pub iid: Option<u64>,
@@ -86,9 +109,74 @@ pub struct Pipeline {
     pub web_url: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    pub source: Option<String>,
+    pub user: Option<User>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// Run time in seconds. Only returned for single pipelines, not in pipeline lists.
+    pub duration: Option<u64>,
+    /// Seconds spent waiting for a runner. Only returned for single pipelines.
+    #[serde(default)]
+    pub queued_duration: Option<f64>,
+    #[serde(default)]
+    pub tag: Option<bool>,
+    #[serde(default)]
+    pub yaml_errors: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl Pipeline {
+    /// IID of the merge request for pipelines on `refs/merge-requests/<iid>/...`.
+    pub fn merge_request_iid(&self) -> Option<u64> {
+        self.ref_name
+            .as_deref()?
+            .strip_prefix("refs/merge-requests/")?
+            .split('/')
+            .next()?
+            .parse()
+            .ok()
+    }
+
+    /// Whether the pipeline ran on a temporary merge of the merge request into its
+    /// target branch (merged results or merge trains) instead of the source commit.
+    pub fn is_merged_result(&self) -> bool {
+        self.merge_request_iid().is_some()
+            && self
+                .ref_name
+                .as_deref()
+                .is_some_and(|r| r.ends_with("/merge") || r.ends_with("/train"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeRequest {
+    pub iid: u64,
+    pub title: Option<String>,
+    /// Tip of the source branch.
+    pub sha: Option<String>,
+    pub source_branch: Option<String>,
+    pub web_url: Option<String>,
+    /// Latest pipeline for the tip, possibly a merged-result one. Only returned for
+    /// single merge requests.
+    #[serde(default)]
+    pub head_pipeline: Option<Pipeline>,
+}
+
+/// A variable passed to a pipeline, e.g. when triggered manually or through the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineVariable {
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub variable_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub username: String,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Job {
     pub id: u64,
     pub name: String,
@@ -99,6 +187,58 @@ pub struct Job {
     pub finished_at: Option<String>,
     pub duration: Option<f64>,
     pub allow_failure: Option<bool>,
+    /// Seconds between creation and pick-up by a runner.
+    #[serde(default)]
+    pub queued_duration: Option<f64>,
+    #[serde(default)]
+    pub failure_reason: Option<String>,
+    #[serde(default)]
+    pub runner: Option<Runner>,
+    #[serde(default)]
+    pub user: Option<User>,
+    /// Runner tags the job requires.
+    #[serde(default)]
+    pub tag_list: Vec<String>,
+    /// Whether a newer attempt of the job exists.
+    #[serde(default)]
+    pub retried: Option<bool>,
+    #[serde(default)]
+    pub coverage: Option<f64>,
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+    /// Trigger job starting a downstream pipeline. Bridges have no log.
+    #[serde(skip)]
+    pub bridge: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Runner {
+    pub id: u64,
+    pub description: Option<String>,
+    pub is_shared: Option<bool>,
+    pub runner_type: Option<String>,
+    /// Only returned by some GitLab versions.
+    #[serde(default)]
+    pub tag_list: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artifact {
+    pub file_type: String,
+    pub filename: Option<String>,
+    pub size: Option<u64>,
+}
+
+/// Result of the CI Lint API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LintResult {
+    pub valid: bool,
+    #[serde(default)]
+    pub errors: Vec<String>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// The configuration with all includes expanded.
+    pub merged_yaml: Option<String>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -115,17 +255,20 @@ impl Stage {
         }
     }
 
+    /// Jobs that have not been superseded by a retry.
+    pub fn current_jobs(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter().filter(|j| !j.retried.unwrap_or(false))
+    }
+
     pub fn status(&self) -> PipelineStatus {
         let mut has_running = false;
         let mut has_pending = false;
         let mut has_failed = false;
 
-        for job in &self.jobs {
+        for job in self.current_jobs() {
             match job.status {
-                PipelineStatus::Failed => {
-                    if !job.allow_failure.unwrap_or(false) {
-                        has_failed = true;
-                    }
+                PipelineStatus::Failed if !job.allow_failure.unwrap_or(false) => {
+                    has_failed = true;
                 }
                 PipelineStatus::Running => has_running = true,
                 PipelineStatus::Pending
@@ -142,14 +285,12 @@ impl Stage {
         } else if has_pending {
             PipelineStatus::Pending
         } else if self
-            .jobs
-            .iter()
+            .current_jobs()
             .all(|j| j.status == PipelineStatus::Success)
         {
             PipelineStatus::Success
         } else if self
-            .jobs
-            .iter()
+            .current_jobs()
             .all(|j| j.status == PipelineStatus::Skipped)
         {
             PipelineStatus::Skipped
@@ -160,12 +301,10 @@ impl Stage {
 
     pub fn has_mixed_failure(&self) -> bool {
         let has_real_failure = self
-            .jobs
-            .iter()
+            .current_jobs()
             .any(|j| j.status == PipelineStatus::Failed && !j.allow_failure.unwrap_or(false));
         let has_non_failure = self
-            .jobs
-            .iter()
+            .current_jobs()
             .any(|j| j.status != PipelineStatus::Failed || j.allow_failure.unwrap_or(false));
         has_real_failure && has_non_failure
     }
@@ -175,6 +314,14 @@ impl Stage {
 pub struct PipelineDetails {
     pub pipeline: Option<Pipeline>,
     pub stages: Vec<Stage>,
+    /// Built from `.gitlab-ci.yml` as no pipeline exists for the commit.
+    pub preview: bool,
+    /// All pipelines of the commit, newest first, e.g. for the branch, a merge
+    /// request, a tag, a schedule and reruns.
+    pub pipelines: Vec<Pipeline>,
+    /// Whether the trigger jobs were fetched. Pipelines fetched only for their status
+    /// in the graph leave them out.
+    pub with_bridges: bool,
 }
 
 impl PipelineDetails {
@@ -195,12 +342,178 @@ impl PipelineDetails {
         stages.sort_by_key(|s| s.jobs.iter().map(|j| j.id).min().unwrap_or(u64::MAX));
 
         Self {
+            pipelines: vec![pipeline.clone()],
             pipeline: Some(pipeline),
             stages,
+            preview: false,
+            with_bridges: false,
         }
     }
 
     pub fn status(&self) -> Option<PipelineStatus> {
         self.pipeline.as_ref().map(|p| p.status)
     }
+
+    /// Status of the pipeline representing the commit under the policy, which is not
+    /// necessarily the shown one.
+    pub fn status_by(&self, policy: &PipelinePolicy) -> Option<PipelineStatus> {
+        policy
+            .select(&self.pipelines)
+            .or(self.pipeline.as_ref())
+            .map(|p| p.status)
+    }
+}
+
+/// Which of several pipelines of a commit represents it, e.g. for its status in the graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PipelinePolicy {
+    /// The most recently created pipeline.
+    #[default]
+    Latest,
+    /// The pipeline whose status needs the most attention, see [`PipelineStatus::severity`].
+    Worst,
+    /// The latest pipeline with the source, e.g. `push` or `merge_request_event`,
+    /// or the latest pipeline if there is none.
+    Source(String),
+}
+
+impl PipelinePolicy {
+    /// Selects a pipeline from a newest-first list.
+    pub fn select<'a>(&self, pipelines: &'a [Pipeline]) -> Option<&'a Pipeline> {
+        match self {
+            Self::Latest => pipelines.first(),
+            Self::Worst => pipelines.iter().min_by_key(|p| p.status.severity()),
+            Self::Source(source) => pipelines
+                .iter()
+                .find(|p| p.source.as_deref() == Some(source.as_str()))
+                .or_else(|| pipelines.first()),
+        }
+    }
+}
+
+impl FromStr for PipelinePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latest" => Ok(Self::Latest),
+            "worst" => Ok(Self::Worst),
+            "" => {
+                Err("Pipeline policy must not be empty. Supports [latest|worst|<source>].".into())
+            }
+            source => Ok(Self::Source(source.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline(id: u64, status: &str, source: &str) -> Pipeline {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "status": status,
+            "sha": "abc",
+            "source": source,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn detects_merge_request_refs() {
+        let cases = [
+            (Some("refs/merge-requests/12/head"), Some(12), false),
+            (Some("refs/merge-requests/12/merge"), Some(12), true),
+            (Some("refs/merge-requests/12/train"), Some(12), true),
+            (Some("main"), None, false),
+            (Some("feature/merge"), None, false),
+            (None, None, false),
+            (Some("refs/merge-requests/abc/merge"), None, false),
+            (Some("refs/merge-requests//merge"), None, false),
+            (Some("refs/merge-requests/"), None, false),
+            (Some("refs/heads/refs/merge-requests/12/merge"), None, false),
+        ];
+        for (ref_name, iid, merged) in cases {
+            let mut pipeline = pipeline(1, "success", "merge_request_event");
+            pipeline.ref_name = ref_name.map(String::from);
+            assert_eq!(pipeline.merge_request_iid(), iid, "{:?}", ref_name);
+            assert_eq!(pipeline.is_merged_result(), merged, "{:?}", ref_name);
+        }
+    }
+
+    #[test]
+    fn selects_pipeline_by_policy() {
+        // Newest first
+        let pipelines = vec![
+            pipeline(5, "success", "push"),
+            pipeline(4, "running", "merge_request_event"),
+            pipeline(3, "failed", "push"),
+            pipeline(2, "failed", "schedule"),
+            pipeline(1, "canceled", "merge_request_event"),
+        ];
+        let source = |s: &str| PipelinePolicy::Source(s.to_string());
+        let cases = [
+            (PipelinePolicy::Latest, Some(5)),
+            // The newest of equally severe pipelines
+            (PipelinePolicy::Worst, Some(3)),
+            (source("merge_request_event"), Some(4)),
+            (source("schedule"), Some(2)),
+            // Falls back to the latest pipeline
+            (source("web"), Some(5)),
+        ];
+        for (policy, expected) in cases {
+            let selected = policy.select(&pipelines).map(|p| p.id);
+            assert_eq!(selected, expected, "{:?}", policy);
+        }
+
+        for policy in [
+            PipelinePolicy::Latest,
+            PipelinePolicy::Worst,
+            source("push"),
+        ] {
+            assert!(policy.select(&[]).is_none(), "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn parses_pipeline_policy() {
+        let cases = [
+            ("latest", Ok(PipelinePolicy::Latest)),
+            ("worst", Ok(PipelinePolicy::Worst)),
+            ("push", Ok(PipelinePolicy::Source("push".to_string()))),
+            (
+                "merge_request_event",
+                Ok(PipelinePolicy::Source("merge_request_event".to_string())),
+            ),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(s.parse::<PipelinePolicy>(), expected, "{}", s);
+        }
+
+        let err = "".parse::<PipelinePolicy>().unwrap_err();
+        assert!(err.contains("[latest|worst|<source>]"), "{}", err);
+    }
+
+    #[test]
+    fn orders_statuses_by_severity() {
+        use PipelineStatus::*;
+        let cases = [
+            (Failed, 0),
+            (Running, 1),
+            (Pending, 1),
+            (WaitingForResource, 1),
+            (Preparing, 1),
+            (Canceled, 2),
+            (Canceling, 2),
+            (Manual, 3),
+            (Created, 3),
+            (Scheduled, 3),
+            (Success, 4),
+            (Skipped, 5),
+        ];
+        for (status, severity) in cases {
+            assert_eq!(status.severity(), severity, "{}", status);
+        }
+    }
 }
@@ -0,0 +1,387 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A source location reported in a job log.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    /// Path as printed in the log, without a leading `./`.
+    pub file: String,
+    pub line: u32,
+    pub column: Option<u32>,
+    pub message: String,
+    pub severity: Severity,
+    /// Index of the log line the problem was found in.
+    pub log_line: usize,
+    /// Name of the matcher that found the problem.
+    pub matcher: &'static str,
+}
+
+/// Recognizes problems in the output of one tool.
+pub trait ProblemMatcher {
+    fn name(&self) -> &'static str;
+
+    /// Checks the line at `index` of the ANSI-stripped log. May look at surrounding lines.
+    fn match_line(&self, lines: &[String], index: usize) -> Option<Problem>;
+}
+
+/// All built-in matchers, most specific first.
+pub fn default_matchers() -> Vec<Box<dyn ProblemMatcher>> {
+    vec![
+        Box::new(RustMatcher),
+        Box::new(GccMatcher),
+        Box::new(PytestMatcher),
+        Box::new(JestMatcher),
+        Box::new(GoMatcher),
+        Box::new(GenericMatcher),
+    ]
+}
+
+/// Scans a log with the given matchers. The first matcher recognizing a line wins;
+/// duplicates (same location and message) are dropped.
+pub fn find_problems(lines: &[String], matchers: &[Box<dyn ProblemMatcher>]) -> Vec<Problem> {
+    let mut seen = HashSet::new();
+    let mut problems = vec![];
+    for index in 0..lines.len() {
+        let problem = matchers.iter().find_map(|m| m.match_line(lines, index));
+        if let Some(problem) = problem {
+            if seen.insert((problem.file.clone(), problem.line, problem.message.clone())) {
+                problems.push(problem);
+            }
+        }
+    }
+    problems
+}
+
+lazy_static! {
+    static ref RUST_LOCATION: Regex =
+        Regex::new(r"^\s*--> (?P<file>[^\s:]+):(?P<line>\d+):(?P<col>\d+)").unwrap();
+    static ref RUST_MESSAGE: Regex =
+        Regex::new(r"^(?P<sev>error|warning)(?:\[\w+\])?: (?P<msg>.+)$").unwrap();
+    static ref RUST_PANIC: Regex = Regex::new(
+        r"panicked at (?:'(?P<msg>.*)', )?(?P<file>[^\s:']+):(?P<line>\d+):(?P<col>\d+)"
+    )
+    .unwrap();
+    static ref GCC: Regex = Regex::new(
+        r"^(?P<file>[^\s:][^:]*):(?P<line>\d+):(?P<col>\d+): (?:fatal )?(?P<sev>error|warning): (?P<msg>.+)$"
+    )
+    .unwrap();
+    static ref PYTEST: Regex =
+        Regex::new(r"^(?P<file>[^\s:]+\.py):(?P<line>\d+): (?P<msg>\w+.*)$").unwrap();
+    static ref JEST_HEADER: Regex = Regex::new(r"^\s*● (?P<msg>.+)$").unwrap();
+    static ref JEST_FRAME: Regex =
+        Regex::new(r"^\s*at .*?\(?(?P<file>[^\s()]+):(?P<line>\d+):(?P<col>\d+)\)?$").unwrap();
+    static ref GO: Regex = Regex::new(
+        r"^\s*(?P<file>[\w./-]+\.go):(?P<line>\d+):(?:(?P<col>\d+):)? (?P<msg>.+)$"
+    )
+    .unwrap();
+    static ref GENERIC: Regex = Regex::new(
+        r"(?P<file>[\w./-]*[\w-]\.[A-Za-z]\w*):(?P<line>\d+)(?::(?P<col>\d+))?"
+    )
+    .unwrap();
+}
+
+/// Extensions accepted by the generic matcher for files without a directory, so that
+/// `host.name:port` isn't taken for a location.
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "c", "cc", "cjs", "cpp", "cs", "css", "cxx", "dart", "ex", "exs", "go", "gradle", "h", "hpp",
+    "hs", "html", "java", "js", "json", "jsx", "kt", "kts", "lua", "m", "mjs", "ml", "php", "pl",
+    "proto", "py", "rb", "rs", "scala", "scss", "sh", "sql", "swift", "tf", "toml", "ts", "tsx",
+    "vue", "xml", "yaml", "yml",
+];
+
+/// Whether a `file.ext` match of the generic matcher looks like a source file: it has a
+/// directory or a known source extension.
+fn is_source_path(file: &str) -> bool {
+    if file.starts_with("//") {
+        // Host name in a URL
+        return false;
+    }
+    let extension = file.rsplit('.').next().unwrap_or_default();
+    file.contains('/') || SOURCE_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+}
+
+fn problem(
+    caps: &regex::Captures,
+    message: &str,
+    severity: Severity,
+    log_line: usize,
+    matcher: &'static str,
+) -> Option<Problem> {
+    let file = caps.name("file")?.as_str();
+    Some(Problem {
+        file: file.strip_prefix("./").unwrap_or(file).to_string(),
+        line: caps.name("line")?.as_str().parse().ok()?,
+        column: caps.name("col").and_then(|c| c.as_str().parse().ok()),
+        message: message.trim().to_string(),
+        severity,
+        log_line,
+        matcher,
+    })
+}
+
+fn severity(text: &str) -> Severity {
+    if text == "warning" {
+        Severity::Warning
+    } else {
+        Severity::Error
+    }
+}
+
+/// rustc/cargo diagnostics (`error[E0308]: ...` followed by ` --> file:line:col`) and panics.
+pub struct RustMatcher;
+
+impl ProblemMatcher for RustMatcher {
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    fn match_line(&self, lines: &[String], index: usize) -> Option<Problem> {
+        let line = &lines[index];
+        if let Some(caps) = RUST_LOCATION.captures(line) {
+            let header = lines[index.saturating_sub(3)..index]
+                .iter()
+                .rev()
+                .find_map(|l| RUST_MESSAGE.captures(l))?;
+            return problem(
+                &caps,
+                &header["msg"],
+                severity(&header["sev"]),
+                index,
+                self.name(),
+            );
+        }
+        let caps = RUST_PANIC.captures(line)?;
+        let message = match caps.name("msg") {
+            Some(msg) => msg.as_str().to_string(),
+            None => lines.get(index + 1).cloned().unwrap_or_default(),
+        };
+        problem(&caps, &message, Severity::Error, index, self.name())
+    }
+}
+
+/// gcc/clang diagnostics (`file:line:col: error: ...`).
+pub struct GccMatcher;
+
+impl ProblemMatcher for GccMatcher {
+    fn name(&self) -> &'static str {
+        "gcc"
+    }
+
+    fn match_line(&self, lines: &[String], index: usize) -> Option<Problem> {
+        let caps = GCC.captures(&lines[index])?;
+        problem(
+            &caps,
+            &caps["msg"],
+            severity(&caps["sev"]),
+            index,
+            self.name(),
+        )
+    }
+}
+
+/// pytest traceback locations (`tests/test_x.py:12: AssertionError`).
+pub struct PytestMatcher;
+
+impl ProblemMatcher for PytestMatcher {
+    fn name(&self) -> &'static str {
+        "pytest"
+    }
+
+    fn match_line(&self, lines: &[String], index: usize) -> Option<Problem> {
+        let caps = PYTEST.captures(&lines[index])?;
+        // The assertion details are printed on the preceding `E` lines
+        let details = lines[index.saturating_sub(10)..index]
+            .iter()
+            .find_map(|l| l.strip_prefix("E "))
+            .map(str::trim);
+        let message = match details {
+            Some(details) => format!("{}: {}", &caps["msg"], details),
+            None => caps["msg"].to_string(),
+        };
+        problem(&caps, &message, Severity::Error, index, self.name())
+    }
+}
+
+/// jest failures (`● Suite › test`) located by their first stack frame outside `node_modules`.
+pub struct JestMatcher;
+
+impl ProblemMatcher for JestMatcher {
+    fn name(&self) -> &'static str {
+        "jest"
+    }
+
+    fn match_line(&self, lines: &[String], index: usize) -> Option<Problem> {
+        let header = JEST_HEADER.captures(&lines[index])?;
+        let frame = lines[index + 1..]
+            .iter()
+            .take(50)
+            .take_while(|l| !JEST_HEADER.is_match(l))
+            .filter_map(|l| JEST_FRAME.captures(l))
+            .find(|caps| !caps["file"].contains("node_modules"))?;
+        problem(&frame, &header["msg"], Severity::Error, index, self.name())
+    }
+}
+
+/// `go test` and `go build`/`go vet` output (`foo_test.go:12: ...`, `./main.go:3:5: ...`).
+pub struct GoMatcher;
+
+impl ProblemMatcher for GoMatcher {
+    fn name(&self) -> &'static str {
+        "go"
+    }
+
+    fn match_line(&self, lines: &[String], index: usize) -> Option<Problem> {
+        let caps = GO.captures(&lines[index])?;
+        problem(&caps, &caps["msg"], Severity::Error, index, self.name())
+    }
+}
+
+/// Any `file.ext:line[:col]` on a line that mentions an error or failure.
+pub struct GenericMatcher;
+
+impl ProblemMatcher for GenericMatcher {
+    fn name(&self) -> &'static str {
+        "generic"
+    }
+
+    fn match_line(&self, lines: &[String], index: usize) -> Option<Problem> {
+        let line = &lines[index];
+        let lower = line.to_lowercase();
+        if !(lower.contains("error") || lower.contains("fail")) {
+            return None;
+        }
+        let caps = GENERIC
+            .captures_iter(line)
+            .find(|caps| is_source_path(&caps["file"]))?;
+        problem(&caps, line, Severity::Error, index, self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(log: &str) -> Vec<Problem> {
+        let lines: Vec<String> = log.lines().map(str::to_string).collect();
+        find_problems(&lines, &default_matchers())
+    }
+
+    fn location(problem: &Problem) -> (&str, u32, Option<u32>) {
+        (problem.file.as_str(), problem.line, problem.column)
+    }
+
+    #[test]
+    fn matches_rustc_diagnostics_and_panics() {
+        let problems = find(
+            "   Compiling app v0.1.0\n\
+             error[E0308]: mismatched types\n  \
+               --> src/main.rs:4:18\n   \
+                |\n\
+             warning: unused variable: `x`\n \
+              --> src/lib.rs:10:9\n\
+             thread 'tests::it_works' panicked at src/lib.rs:42:5:\n\
+             assertion failed: false",
+        );
+        assert_eq!(problems.len(), 3);
+        assert!(problems.iter().all(|p| p.matcher == "rust"));
+        assert_eq!(location(&problems[0]), ("src/main.rs", 4, Some(18)));
+        assert_eq!(problems[0].message, "mismatched types");
+        assert_eq!(problems[0].severity, Severity::Error);
+        assert_eq!(location(&problems[1]), ("src/lib.rs", 10, Some(9)));
+        assert_eq!(problems[1].severity, Severity::Warning);
+        assert_eq!(location(&problems[2]), ("src/lib.rs", 42, Some(5)));
+        assert_eq!(problems[2].message, "assertion failed: false");
+    }
+
+    #[test]
+    fn matches_gcc_and_clang_diagnostics() {
+        let problems = find(
+            "src/parser.c:17:5: error: 'x' undeclared (first use in this function)\n\
+             ./include/util.h:3:10: fatal error: missing.h: No such file or directory\n\
+             src/main.cpp:8:12: warning: unused variable 'y' [-Wunused-variable]",
+        );
+        assert_eq!(problems.len(), 3);
+        assert!(problems.iter().all(|p| p.matcher == "gcc"));
+        assert_eq!(location(&problems[0]), ("src/parser.c", 17, Some(5)));
+        assert_eq!(location(&problems[1]), ("include/util.h", 3, Some(10)));
+        assert_eq!(problems[2].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn matches_pytest_failures_with_assertion_details() {
+        let problems = find(
+            "    def test_add():\n\
+             >       assert add(1, 2) == 4\n\
+             E       assert 3 == 4\n\
+             \n\
+             tests/test_math.py:7: AssertionError",
+        );
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].matcher, "pytest");
+        assert_eq!(location(&problems[0]), ("tests/test_math.py", 7, None));
+        assert_eq!(problems[0].message, "AssertionError: assert 3 == 4");
+    }
+
+    #[test]
+    fn matches_jest_failures_outside_node_modules() {
+        let problems = find(
+            "  ● Math › adds numbers\n\
+             \n    \
+                 expect(received).toBe(expected)\n\
+             \n      \
+                   at Object.toBe (node_modules/expect/build/index.js:10:3)\n      \
+                   at Object.<anonymous> (src/math.test.js:5:19)",
+        );
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].matcher, "jest");
+        assert_eq!(location(&problems[0]), ("src/math.test.js", 5, Some(19)));
+        assert_eq!(problems[0].message, "Math › adds numbers");
+    }
+
+    #[test]
+    fn matches_go_test_and_build_output() {
+        let problems = find(
+            "--- FAIL: TestAdd (0.00s)\n    \
+                 math_test.go:12: expected 4, got 3\n\
+             ./main.go:3:5: undefined: foo",
+        );
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().all(|p| p.matcher == "go"));
+        assert_eq!(location(&problems[0]), ("math_test.go", 12, None));
+        assert_eq!(problems[0].message, "expected 4, got 3");
+        assert_eq!(location(&problems[1]), ("main.go", 3, Some(5)));
+    }
+
+    #[test]
+    fn generic_matcher_needs_an_error_and_a_source_path() {
+        let problems = find(
+            "Error in config/settings.yml:14 (invalid key)\n\
+             Lint failed at Main.java:20:3\n\
+             Processing app/models/user.rb:5\n\
+             ERROR: failed to push registry.example.com:5000/group/image:latest\n\
+             error: could not connect to https://gitlab.example.com:443/api\n\
+             fail: db.internal:5432 refused the connection",
+        );
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().all(|p| p.matcher == "generic"));
+        assert_eq!(location(&problems[0]), ("config/settings.yml", 14, None));
+        assert_eq!(location(&problems[1]), ("Main.java", 20, Some(3)));
+    }
+
+    #[test]
+    fn drops_duplicate_problems() {
+        let problems = find(
+            "src/a.c:1:1: error: boom\n\
+             src/a.c:1:1: error: boom\n\
+             src/a.c:1:1: error: other",
+        );
+        assert_eq!(problems.len(), 2);
+    }
+}
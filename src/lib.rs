@@ -1,4 +1,6 @@
 pub mod app;
+pub mod bisect;
+pub mod cli;
 pub mod dialogs;
 pub mod gitlab;
 pub mod gitlab_config;
@@ -1,4 +1,4 @@
-use clap::{crate_version, Arg, Command};
+use clap::{crate_version, Arg, ArgMatches, Command};
 use crossterm::{
     event::{self, Event as CEvent, KeyCode, KeyModifiers},
     execute,
@@ -16,25 +16,36 @@ use git_graph::{
     },
 };
 use git_igitt::app::DiffMode;
+use git_igitt::cli::{
+    run_pipeline_command, OutputFormat, PipelineAction, PipelineCommand, EXIT_ERROR,
+};
+use git_igitt::gitlab::models::PipelinePolicy;
 use git_igitt::gitlab::GitLabClient;
 use git_igitt::settings::AppSettings;
+use git_igitt::widgets::graph_view::StatusGroup;
 use git_igitt::{
     app::{
-        ActiveView, App, CurrentBranches, JobLogRequest, JobLogResponse, PipelineRequest,
-        PipelineResponse, DEFAULT_PIPELINE_LOAD_LIMIT,
+        ActiveView, AnalyticsRequest, AnalyticsResponse, App, CompareRequest, CompareResponse,
+        CurrentBranches, HistoryRequest, HistoryResponse, JobLogRequest, JobLogResponse,
+        LintRequest, LintResponse, MergeRequestPipelinesRequest, MergeRequestPipelinesResponse,
+        PipelineInfoRequest, PipelineInfoResponse, PipelineRequest, PipelineResponse,
+        DEFAULT_PIPELINE_LOAD_LIMIT,
     },
     dialogs::FileDialog,
     ui,
 };
 use platform_dirs::AppDirs;
-use ratatui::{backend::CrosstermBackend, Terminal};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    Terminal,
+};
 use std::cell::Cell;
 use std::sync::mpsc;
 use std::thread;
 use std::time::Instant;
 use std::{
     error::Error,
-    io::stdout,
+    io::{stdout, IsTerminal, Write},
     path::{Path, PathBuf},
     str::FromStr,
     time::Duration,
@@ -46,6 +57,9 @@ const PIPELINE_REFRESH_RATE: u64 = 3000;
 const ANIMATION_TICK_RATE: u64 = 200;
 const JOB_LOG_REFRESH_RATE: u64 = 1000;
 const HEAD_PIPELINE_RECHECK_RATE: u64 = 5000;
+const HISTORY_PIPELINE_COUNT: usize = 30;
+const ANALYTICS_PIPELINE_COUNT: usize = 30;
+const PIPELINE_WATCH_INTERVAL: u64 = 10;
 const INITIAL_KEY_REPEAT_TIME: u128 = 100;
 const MIN_KEY_REPEAT_TIME: u128 = 50;
 
@@ -62,6 +76,51 @@ fn reset_terminal() -> std::result::Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Runs a program from an environment variable (or the fallback) on a file,
+/// with the TUI suspended while it runs.
+fn run_external<B: Backend + Write>(
+    terminal: &mut Terminal<B>,
+    env_vars: &[&str],
+    fallback: &str,
+    path: &Path,
+) -> Result<Result<(), String>, Box<dyn Error>> {
+    let program = env_vars
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| fallback.to_string());
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    let status = if cfg!(windows) {
+        std::process::Command::new("cmd")
+            .arg("/C")
+            .arg(&program)
+            .arg(path)
+            .status()
+    } else {
+        std::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$1\"", program))
+            .arg("sh")
+            .arg(path)
+            .status()
+    };
+
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    terminal.hide_cursor()?;
+    terminal.clear()?;
+
+    Ok(match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("'{}' exited with {}", program, status)),
+        Err(err) => Err(format!("Failed to run '{}': {}", program, err)),
+    })
+}
+
 fn chain_panic_hook() {
     let original_hook = std::panic::take_hook();
 
@@ -74,12 +133,14 @@ fn chain_panic_hook() {
 fn main() {
     chain_panic_hook();
     std::process::exit(match from_args() {
-        Ok(_) => 0,
+        Ok(code) => code,
         Err(err) => {
             let mut sout = stdout();
-            match execute!(sout, LeaveAlternateScreen) {
-                Ok(_) => {}
-                Err(err) => eprintln!("{}", err),
+            // Don't write escape sequences into the output of headless commands
+            if sout.is_terminal() {
+                if let Err(err) = execute!(sout, LeaveAlternateScreen) {
+                    eprintln!("{}", err);
+                }
             }
             eprintln!("{}", err);
             1
@@ -87,6 +148,53 @@ fn main() {
     });
 }
 
+/// Parses the options of the headless `pipeline` subcommand and runs it.
+fn pipeline_command(
+    repository: Result<Repository, git2::Error>,
+    app_matches: &ArgMatches,
+    matches: &ArgMatches,
+    colored: bool,
+    pipeline_policy: PipelinePolicy,
+) -> Result<i32, String> {
+    let repository = repository.map_err(|err| format!("ERROR: {}\n       Navigate into a repository before running git-igitt, or use option --path", err.message()))?;
+    let interval = match matches.get_one::<String>("interval") {
+        None => PIPELINE_WATCH_INTERVAL,
+        Some(str) => match str.parse::<u64>() {
+            Ok(val) if val > 0 => val,
+            _ => {
+                return Err(format![
+                    "Option interval must be a positive number, but got '{}'",
+                    str
+                ])
+            }
+        },
+    };
+    let command = PipelineCommand {
+        action: matches
+            .get_one::<String>("command")
+            .map(|s| PipelineAction::from_str(s))
+            .unwrap_or(Ok(PipelineAction::Status))?,
+        rev: matches
+            .get_one::<String>("rev")
+            .cloned()
+            .unwrap_or_else(|| "HEAD".to_string()),
+        format: matches
+            .get_one::<String>("format")
+            .map(|s| OutputFormat::from_str(s))
+            .unwrap_or(Ok(OutputFormat::Text))?,
+        job: matches.get_one::<String>("job").cloned(),
+        interval: Duration::from_secs(interval),
+        // Keep job log colors only when they end up in a terminal, unless forced
+        colored: colored
+            && (matches!(
+                app_matches.get_one::<String>("color").map(String::as_str),
+                Some("always")
+            ) || stdout().is_terminal()),
+        policy: pipeline_policy,
+    };
+    run_pipeline_command(&repository, &command)
+}
+
 fn setup_logger(log_level: &str) {
     let level = match log_level {
         "error" => log::LevelFilter::Error,
@@ -126,7 +234,7 @@ fn setup_logger(log_level: &str) {
     let _handle = log4rs::init_config(config).unwrap();
 }
 
-fn from_args() -> Result<(), String> {
+fn from_args() -> Result<i32, String> {
     let app_dir = AppDirs::new(Some("git-graph"), false).unwrap().config_dir;
     let mut models_dir = app_dir;
     models_dir.push("models");
@@ -145,7 +253,8 @@ fn from_args() -> Result<(), String> {
                  git-graph --model <model>   -> Start application using a certain <model>\n    \
                  git-graph model --list      -> List available branching models\n    \
                  git-graph model             -> Show repo's current branching models\n    \
-                 git-graph model <model>     -> Permanently set model <model> for this repo",
+                 git-graph model <model>     -> Permanently set model <model> for this repo\n    \
+                 git-igitt pipeline watch    -> Wait for the pipeline of HEAD, fail if it fails",
         )
         .arg(
             Arg::new("path")
@@ -292,6 +401,16 @@ fn from_args() -> Result<(), String> {
                 .num_args(1)
                 .value_name("count"),
         )
+        .arg(
+            Arg::new("pipeline-policy")
+                .long("pipeline-policy")
+                .help("Pipeline shown for commits with several pipelines, and whose status the graph shows.\n\
+                       Supports [latest|worst|<source>], where <source> is a pipeline source like\n\
+                       push, merge_request_event or schedule. Default: latest.")
+                .required(false)
+                .num_args(1)
+                .value_name("policy"),
+        )
         .subcommand(Command::new("model")
             .about("Prints or permanently sets the branching model for a repository.")
             .arg(
@@ -309,6 +428,48 @@ fn from_args() -> Result<(), String> {
                     .help("List all available branching models.")
                     .required(false)
                     .num_args(0),
+            ))
+        .subcommand(Command::new("pipeline")
+            .about("Prints the GitLab pipeline of a commit without starting the UI.")
+            .arg(
+                Arg::new("command")
+                    .help("One of [status|jobs|log|watch]. Default: status.\n\
+                           watch blocks until the pipeline finishes and exits with\n\
+                           1 if it did not succeed. Errors exit with 2.")
+                    .value_name("command")
+                    .num_args(1)
+                    .required(false)
+                    .index(1))
+            .arg(
+                Arg::new("rev")
+                    .long("rev")
+                    .help("Revision whose pipeline is shown. Default: HEAD.")
+                    .required(false)
+                    .num_args(1)
+                    .value_name("rev"),
+            )
+            .arg(
+                Arg::new("format")
+                    .long("format")
+                    .help("Output format. One of [text|json]. Default: text.")
+                    .required(false)
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("job")
+                    .long("job")
+                    .help("Job name or ID for 'log'. Default: the first failed job, or the last job.")
+                    .required(false)
+                    .num_args(1)
+                    .value_name("job"),
+            )
+            .arg(
+                Arg::new("interval")
+                    .long("interval")
+                    .help("Poll interval in seconds for 'watch'. Default: 10.")
+                    .required(false)
+                    .num_args(1)
+                    .value_name("seconds"),
             ));
 
     let matches = app.get_matches();
@@ -319,7 +480,7 @@ fn from_args() -> Result<(), String> {
                 "{}",
                 itertools::join(get_available_models(&models_dir)?, "\n")
             );
-            return Ok(());
+            return Ok(0);
         }
     }
 
@@ -341,7 +502,7 @@ fn from_args() -> Result<(), String> {
                     }
                     Some(model) => set_model(&repository, model, REPO_CONFIG_FILE, &models_dir)?,
                 };
-                return Ok(());
+                return Ok(0);
             }
             Err(err) => return Err(format!("ERROR: {}\n       Navigate into a repository before running git-graph, or use option --path", err.message())),
         }
@@ -385,6 +546,11 @@ fn from_args() -> Result<(), String> {
         },
     };
 
+    let pipeline_policy = match matches.get_one::<String>("pipeline-policy") {
+        None => PipelinePolicy::default(),
+        Some(str) => PipelinePolicy::from_str(str)?,
+    };
+
     let include_remote = !matches.get_flag("local");
     let reverse_commit_order = matches.get_flag("reverse");
 
@@ -436,6 +602,21 @@ fn from_args() -> Result<(), String> {
         }
     };
 
+    if let Some(pipeline_matches) = matches.subcommand_matches("pipeline") {
+        // Exit code 1 is left to pipelines that did not succeed
+        return Ok(pipeline_command(
+            repository,
+            &matches,
+            pipeline_matches,
+            colored,
+            pipeline_policy,
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            EXIT_ERROR
+        }));
+    }
+
     let app_settings = AppSettings::default().tab_width(tab_width.unwrap_or(4));
 
     let settings = Settings {
@@ -459,10 +640,11 @@ fn from_args() -> Result<(), String> {
         model.map(|x| &**x),
         commit_limit,
         pipeline_load_limit,
+        pipeline_policy,
     )
     .map_err(|err| err.to_string())?;
 
-    Ok(())
+    Ok(0)
 }
 
 fn run(
@@ -472,6 +654,7 @@ fn run(
     model: Option<&str>,
     max_commits: Option<usize>,
     pipeline_load_limit: usize,
+    pipeline_policy: PipelinePolicy,
 ) -> Result<(), Box<dyn Error>> {
     enable_raw_mode()?;
 
@@ -491,8 +674,17 @@ fn run(
     let batch_response_tx = pipeline_response_tx.clone();
     thread::spawn(move || {
         while let Ok(req) = pipeline_request_rx.recv() {
-            let result = GitLabClient::new(&req.base_url, &req.token)
-                .and_then(|client| client.get_pipeline_details(&req.project_id, &req.sha));
+            let result = GitLabClient::new(&req.base_url, &req.token).and_then(|client| {
+                client.get_pipeline_details(
+                    &req.project_id,
+                    &req.sha,
+                    &req.policy,
+                    req.pipeline_id,
+                    req.include_retried,
+                    req.with_bridges,
+                    &req.merge_request_pipelines,
+                )
+            });
             let _ = batch_response_tx.send(PipelineResponse {
                 sha: req.sha,
                 result,
@@ -503,8 +695,17 @@ fn run(
     let (head_pipeline_tx, head_pipeline_rx) = mpsc::channel::<PipelineRequest>();
     thread::spawn(move || {
         while let Ok(req) = head_pipeline_rx.recv() {
-            let result = GitLabClient::new(&req.base_url, &req.token)
-                .and_then(|client| client.get_pipeline_details(&req.project_id, &req.sha));
+            let result = GitLabClient::new(&req.base_url, &req.token).and_then(|client| {
+                client.get_pipeline_details(
+                    &req.project_id,
+                    &req.sha,
+                    &req.policy,
+                    req.pipeline_id,
+                    req.include_retried,
+                    req.with_bridges,
+                    &req.merge_request_pipelines,
+                )
+            });
             let _ = pipeline_response_tx.send(PipelineResponse {
                 sha: req.sha,
                 result,
@@ -527,6 +728,130 @@ fn run(
         }
     });
 
+    let (history_request_tx, history_request_rx) = mpsc::channel::<HistoryRequest>();
+    let (history_response_tx, history_response_rx) = mpsc::channel::<HistoryResponse>();
+
+    thread::spawn(move || {
+        while let Ok(req) = history_request_rx.recv() {
+            let result = GitLabClient::new(&req.base_url, &req.token).and_then(|client| {
+                client.get_ref_pipelines(&req.project_id, &req.ref_name, HISTORY_PIPELINE_COUNT)
+            });
+            let _ = history_response_tx.send(HistoryResponse {
+                ref_name: req.ref_name,
+                result,
+            });
+        }
+    });
+
+    let (analytics_request_tx, analytics_request_rx) = mpsc::channel::<AnalyticsRequest>();
+    let (analytics_response_tx, analytics_response_rx) = mpsc::channel::<AnalyticsResponse>();
+
+    thread::spawn(move || {
+        while let Ok(req) = analytics_request_rx.recv() {
+            let result = GitLabClient::new(&req.base_url, &req.token).and_then(|client| {
+                client.get_ref_job_analytics(
+                    &req.project_id,
+                    &req.ref_name,
+                    ANALYTICS_PIPELINE_COUNT,
+                )
+            });
+            let _ = analytics_response_tx.send(AnalyticsResponse {
+                ref_name: req.ref_name,
+                result,
+            });
+        }
+    });
+
+    let (lint_request_tx, lint_request_rx) = mpsc::channel::<LintRequest>();
+    let (lint_response_tx, lint_response_rx) = mpsc::channel::<LintResponse>();
+
+    thread::spawn(move || {
+        while let Ok(req) = lint_request_rx.recv() {
+            let result = GitLabClient::new(&req.base_url, &req.token).and_then(|client| {
+                client.lint_ci_config(&req.project_id, &req.content, req.ref_name.as_deref())
+            });
+            let _ = lint_response_tx.send(LintResponse {
+                sha: req.sha,
+                result,
+            });
+        }
+    });
+
+    let (pipeline_info_request_tx, pipeline_info_request_rx) =
+        mpsc::channel::<PipelineInfoRequest>();
+    let (pipeline_info_response_tx, pipeline_info_response_rx) =
+        mpsc::channel::<PipelineInfoResponse>();
+
+    thread::spawn(move || {
+        while let Ok(req) = pipeline_info_request_rx.recv() {
+            let client = GitLabClient::new(&req.base_url, &req.token);
+            let result = client
+                .as_ref()
+                .map_err(|e| e.clone())
+                .and_then(|client| client.get_pipeline(&req.project_id, req.pipeline_id));
+            let variables = if req.with_variables {
+                Some(client.as_ref().map_err(|e| e.clone()).and_then(|client| {
+                    client.get_pipeline_variables(&req.project_id, req.pipeline_id)
+                }))
+            } else {
+                None
+            };
+            let _ = pipeline_info_response_tx.send(PipelineInfoResponse {
+                pipeline_id: req.pipeline_id,
+                result,
+                variables,
+            });
+        }
+    });
+
+    let (compare_request_tx, compare_request_rx) = mpsc::channel::<CompareRequest>();
+    let (compare_response_tx, compare_response_rx) = mpsc::channel::<CompareResponse>();
+
+    thread::spawn(move || {
+        while let Ok(req) = compare_request_rx.recv() {
+            let result = GitLabClient::new(&req.base_url, &req.token).and_then(|client| {
+                Ok((
+                    client.get_pipeline_details(
+                        &req.project_id,
+                        &req.base_sha,
+                        &req.policy,
+                        None,
+                        false,
+                        true,
+                        &req.base_merge_request_pipelines,
+                    )?,
+                    client.get_pipeline_details(
+                        &req.project_id,
+                        &req.head_sha,
+                        &req.policy,
+                        None,
+                        false,
+                        true,
+                        &req.head_merge_request_pipelines,
+                    )?,
+                ))
+            });
+            let _ = compare_response_tx.send(CompareResponse {
+                base_sha: req.base_sha,
+                head_sha: req.head_sha,
+                result,
+            });
+        }
+    });
+
+    let (merge_request_tx, merge_request_rx) = mpsc::channel::<MergeRequestPipelinesRequest>();
+    let (merge_request_response_tx, merge_request_response_rx) =
+        mpsc::channel::<MergeRequestPipelinesResponse>();
+
+    thread::spawn(move || {
+        while let Ok(req) = merge_request_rx.recv() {
+            let result = GitLabClient::new(&req.base_url, &req.token).and_then(|client| {
+                client.get_merge_request_pipelines_by_sha(&req.project_id, &req.branches)
+            });
+            let _ = merge_request_response_tx.send(MergeRequestPipelinesResponse { result });
+        }
+    });
+
     let mut file_dialog =
         FileDialog::new("Open repository", settings.colored).map_err(|err| err.to_string())?;
     if let Some(repo) = &repository {
@@ -549,9 +874,16 @@ fn run(
         } else {
             let mut app = create_app(repository, &mut settings, &app_settings, model, max_commits)?;
             app.pipeline_load_limit = pipeline_load_limit;
+            app.pipeline_policy = pipeline_policy.clone();
             app.set_pipeline_channel(pipeline_request_tx.clone());
             app.set_head_pipeline_channel(head_pipeline_tx.clone());
             app.set_job_log_channel(job_log_request_tx.clone());
+            app.set_history_channel(history_request_tx.clone());
+            app.set_analytics_channel(analytics_request_tx.clone());
+            app.set_lint_channel(lint_request_tx.clone());
+            app.set_pipeline_info_channel(pipeline_info_request_tx.clone());
+            app.set_compare_channel(compare_request_tx.clone());
+            app.set_merge_request_channel(merge_request_tx.clone());
             app.request_batch_pipelines();
             app.active_view = ActiveView::Logo;
             Some(app)
@@ -604,12 +936,10 @@ fn run(
                 match event::read().unwrap() {
                     CEvent::Key(key) => return Event::Input(key),
                     CEvent::Mouse(_) => (),
-                    CEvent::Resize(sx, sy) => {
-                        if sx != sx_old || sy != sy_old {
-                            sx_old = sx;
-                            sy_old = sy;
-                            return Event::Resize;
-                        }
+                    CEvent::Resize(sx, sy) if sx != sx_old || sy != sy_old => {
+                        sx_old = sx;
+                        sy_old = sy;
+                        return Event::Resize;
                     }
                     _ => {}
                 }
@@ -723,6 +1053,49 @@ fn run(
                     }
                     Event::Update => {}
                 }
+            } else if app.active_view == ActiveView::Pipeline && app.pipeline_state.job_details_open
+            {
+                match next_event() {
+                    Event::Input(event) => {
+                        match event.code {
+                            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('i') => {
+                                app.pipeline_state.toggle_job_details()
+                            }
+                            KeyCode::Char('q') => {
+                                disable_raw_mode()?;
+                                execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+                                terminal.show_cursor()?;
+                                break;
+                            }
+                            _ => {}
+                        }
+                        needs_redraw = true;
+                    }
+                    Event::Resize => {
+                        needs_redraw = true;
+                    }
+                    Event::Update => {}
+                }
+            } else if app.active_view == ActiveView::Pipeline
+                && app.pipeline_state.log_search.editing
+            {
+                match next_event() {
+                    Event::Input(event) => {
+                        match event.code {
+                            KeyCode::Char(c) => app.pipeline_state.log_search_input(c),
+                            KeyCode::Backspace => app.pipeline_state.log_search_backspace(),
+                            KeyCode::Tab => app.pipeline_state.toggle_log_search_regex(),
+                            KeyCode::Enter => app.pipeline_state.close_log_search(true),
+                            KeyCode::Esc => app.pipeline_state.close_log_search(false),
+                            _ => {}
+                        }
+                        needs_redraw = true;
+                    }
+                    Event::Resize => {
+                        needs_redraw = true;
+                    }
+                    Event::Update => {}
+                }
             } else if app.active_view == ActiveView::Search {
                 match next_event() {
                     Event::Input(event) => {
@@ -757,6 +1130,8 @@ fn run(
                             last_key = event.code;
                         }
                         last_key_time = now;
+                        // Notices are shown until the next key press
+                        app.pipeline_state.job_log_notice = None;
 
                         match event.code {
                             KeyCode::Char('q') => {
@@ -784,6 +1159,78 @@ fn run(
                                 app = app.reload(&settings, max_commits)?;
                                 app.request_batch_pipelines();
                             }
+                            KeyCode::Char('R') => match app.active_view {
+                                ActiveView::Models
+                                | ActiveView::Remotes
+                                | ActiveView::Search
+                                | ActiveView::Help(_) => {}
+                                _ => {
+                                    if let Err(err) = app.select_remote() {
+                                        app.set_error(err);
+                                    }
+                                }
+                            },
+                            KeyCode::Char('H') => match app.active_view {
+                                ActiveView::Models
+                                | ActiveView::Remotes
+                                | ActiveView::History
+                                | ActiveView::Analytics
+                                | ActiveView::Problems
+                                | ActiveView::Lint
+                                | ActiveView::Compare
+                                | ActiveView::Search
+                                | ActiveView::Help(_) => {}
+                                _ => {
+                                    if let Err(err) = app.show_history() {
+                                        app.set_error(err);
+                                    }
+                                }
+                            },
+                            KeyCode::Char('A') => match app.active_view {
+                                ActiveView::Models
+                                | ActiveView::Remotes
+                                | ActiveView::History
+                                | ActiveView::Analytics
+                                | ActiveView::Problems
+                                | ActiveView::Lint
+                                | ActiveView::Compare
+                                | ActiveView::Search
+                                | ActiveView::Help(_) => {}
+                                _ => {
+                                    if let Err(err) = app.show_analytics() {
+                                        app.set_error(err);
+                                    }
+                                }
+                            },
+                            KeyCode::Char('Y') => match app.active_view {
+                                ActiveView::Models
+                                | ActiveView::Remotes
+                                | ActiveView::History
+                                | ActiveView::Analytics
+                                | ActiveView::Problems
+                                | ActiveView::Lint
+                                | ActiveView::Compare
+                                | ActiveView::Search
+                                | ActiveView::Help(_) => {}
+                                _ => {
+                                    if let Err(err) = app.show_lint() {
+                                        app.set_error(err);
+                                    }
+                                }
+                            },
+                            KeyCode::Char('=') => match app.active_view {
+                                ActiveView::Graph | ActiveView::Commit | ActiveView::Pipeline => {
+                                    if let Err(err) = app.show_compare() {
+                                        app.set_error(err);
+                                    }
+                                }
+                                _ => {}
+                            },
+                            KeyCode::Char('F') if app.active_view == ActiveView::Pipeline => {
+                                if let Err(err) = app.show_problems() {
+                                    app.set_error(err);
+                                }
+                            }
                             KeyCode::Char('l') => {
                                 if event.modifiers.contains(KeyModifiers::CONTROL) {
                                     app.toggle_line_numbers()?;
@@ -794,28 +1241,66 @@ fn run(
                                     app.toggle_layout();
                                 }
                             }
-                            KeyCode::Char('w') => {
-                                if event.modifiers.contains(KeyModifiers::CONTROL) {
-                                    app.toggle_line_wrap()?;
+                            KeyCode::Char('w')
+                                if event.modifiers.contains(KeyModifiers::CONTROL) =>
+                            {
+                                app.toggle_line_wrap()?;
+                            }
+                            KeyCode::Char('S') if app.active_view == ActiveView::Branches => {
+                                app.cycle_branch_sort();
+                            }
+                            KeyCode::Char('w') if app.active_view == ActiveView::Branches => {
+                                if let Err(err) = app.toggle_watch_branch() {
+                                    app.set_error(err);
                                 }
                             }
-                            KeyCode::Char('c') => {
-                                if app.active_view == ActiveView::Pipeline
-                                    && app.pipeline_state.job_log_focused
-                                    && !app.pipeline_state.job_log.is_empty()
-                                {
-                                    let text = app.pipeline_state.job_log_as_text();
-                                    if let Ok(mut child) = std::process::Command::new("pbcopy")
-                                        .stdin(std::process::Stdio::piped())
-                                        .spawn()
-                                    {
-                                        if let Some(stdin) = child.stdin.take() {
-                                            use std::io::Write;
-                                            let mut stdin = stdin;
-                                            let _ = stdin.write_all(text.as_bytes());
-                                        }
-                                        let _ = child.wait();
-                                    }
+                            KeyCode::Char('c') | KeyCode::Char('C') => {
+                                let secondary = matches!(event.code, KeyCode::Char('C'));
+                                if let Err(err) = app.copy_to_clipboard(secondary) {
+                                    app.set_error(err);
+                                }
+                            }
+                            KeyCode::Char('O') | KeyCode::Char('U') => {
+                                let copy = event.code == KeyCode::Char('U');
+                                if let Err(err) = app.open_web_url(copy) {
+                                    app.set_error(err);
+                                }
+                            }
+                            KeyCode::Char(c @ ('[' | ']' | '{' | '}' | '(' | ')'))
+                                if matches!(
+                                    app.active_view,
+                                    ActiveView::Graph | ActiveView::Pipeline
+                                ) =>
+                            {
+                                let group = match c {
+                                    '[' | ']' => StatusGroup::Failed,
+                                    '{' | '}' => StatusGroup::Running,
+                                    _ => StatusGroup::Passed,
+                                };
+                                let older = matches!(c, ']' | '}' | ')');
+                                reload_diffs = app.select_commit_by_status(group, older);
+                            }
+                            KeyCode::Char('*') if app.active_view == ActiveView::Graph => {
+                                app.cycle_graph_filter();
+                            }
+                            KeyCode::Char('g')
+                                if event.modifiers.contains(KeyModifiers::CONTROL) =>
+                            {
+                                if let Err(err) = app.bisect_start() {
+                                    app.set_error(err);
+                                }
+                            }
+                            KeyCode::Char('g') | KeyCode::Char('B')
+                                if app.active_view == ActiveView::Graph =>
+                            {
+                                match app.bisect_mark(event.code == KeyCode::Char('g')) {
+                                    Ok(reload) => reload_diffs = reload,
+                                    Err(err) => app.set_error(err),
+                                }
+                            }
+                            KeyCode::Char('G') if app.active_view == ActiveView::Graph => {
+                                if let Err(err) = app.bisect_reset() {
+                                    app.set_error(err);
                                 }
                             }
                             KeyCode::Char('b') => app.toggle_branches(),
@@ -851,6 +1336,9 @@ fn run(
                                     _ => app.open_search(),
                                 }
                             }
+                            KeyCode::Char('f') if app.active_view == ActiveView::Branches => {
+                                app.cycle_branch_filter();
+                            }
                             KeyCode::F(3) => match app.active_view {
                                 ActiveView::Models | ActiveView::Search | ActiveView::Help(_) => {}
                                 _ => {
@@ -861,6 +1349,87 @@ fn run(
                                     }
                                 }
                             },
+                            KeyCode::Char('i') if app.active_view == ActiveView::Pipeline => {
+                                app.pipeline_state.toggle_job_details();
+                            }
+                            KeyCode::Char('<') if app.active_view == ActiveView::Pipeline => {
+                                app.switch_pipeline(true);
+                            }
+                            KeyCode::Char('>') if app.active_view == ActiveView::Pipeline => {
+                                app.switch_pipeline(false);
+                            }
+                            KeyCode::Char('I') if app.active_view == ActiveView::Pipeline => {
+                                app.toggle_include_retried();
+                            }
+                            KeyCode::Char('t') if app.active_view == ActiveView::Pipeline => {
+                                app.pipeline_state.toggle_timeline();
+                            }
+                            KeyCode::Char('V') if app.active_view == ActiveView::Pipeline => {
+                                app.toggle_pipeline_variables();
+                            }
+                            KeyCode::Char('/') if app.active_view == ActiveView::Pipeline => {
+                                app.pipeline_state.open_log_search();
+                            }
+                            KeyCode::Char('n') | KeyCode::Char('N')
+                                if app.active_view == ActiveView::Pipeline
+                                    && app.pipeline_state.job_log_focused
+                                    && !app.pipeline_state.log_search.matches.is_empty() =>
+                            {
+                                app.pipeline_state
+                                    .next_log_match(event.code == KeyCode::Char('n'));
+                            }
+                            KeyCode::Char('z') if app.active_view == ActiveView::Pipeline => {
+                                app.pipeline_state.toggle_log_section();
+                            }
+                            KeyCode::Char('Z') if app.active_view == ActiveView::Pipeline => {
+                                app.pipeline_state.toggle_all_log_sections();
+                            }
+                            KeyCode::Char('x') | KeyCode::Char('X')
+                                if app.active_view == ActiveView::Pipeline =>
+                            {
+                                if let Err(err) =
+                                    app.export_job_log(event.code == KeyCode::Char('X'))
+                                {
+                                    app.set_error(err);
+                                }
+                            }
+                            KeyCode::Char('v') | KeyCode::Char('E')
+                                if app.active_view == ActiveView::Pipeline =>
+                            {
+                                let is_pager = event.code == KeyCode::Char('v');
+                                match app.job_log_temp_file(is_pager) {
+                                    Ok(Some(path)) => {
+                                        let result = if is_pager {
+                                            run_external(
+                                                &mut terminal,
+                                                &["PAGER"],
+                                                "less -R",
+                                                &path,
+                                            )?
+                                        } else {
+                                            run_external(
+                                                &mut terminal,
+                                                &["VISUAL", "EDITOR"],
+                                                if cfg!(windows) { "notepad" } else { "vi" },
+                                                &path,
+                                            )?
+                                        };
+                                        drop(path);
+                                        if let Err(err) = result {
+                                            app.set_error(err);
+                                        }
+                                    }
+                                    Ok(None) => {}
+                                    Err(err) => app.set_error(err),
+                                }
+                            }
+                            KeyCode::Char('e') if app.active_view == ActiveView::Pipeline => {
+                                let found = app.pipeline_state.jump_to_first_error();
+                                if !found {
+                                    app.pipeline_state.job_log_notice =
+                                        Some("no error found".to_string());
+                                }
+                            }
                             KeyCode::Char('n') => {
                                 let reset = app.diff_options.diff_mode == DiffMode::Diff;
                                 reload_file = app.set_diff_mode(DiffMode::New)?;
@@ -950,15 +1519,25 @@ fn run(
                                         app.set_error(err);
                                         app.active_view = ActiveView::Graph;
                                     }
+                                } else if app.active_view == ActiveView::Remotes {
+                                    if let Err(err) = app.set_remote() {
+                                        app.set_error(err);
+                                    }
+                                } else if app.active_view == ActiveView::History {
+                                    if let Err(err) = app.select_history_pipeline() {
+                                        app.set_error(err);
+                                    }
+                                } else if app.active_view == ActiveView::Problems {
+                                    if let Err(err) = app.open_problem() {
+                                        app.set_error(err);
+                                    }
                                 } else {
                                     reload_diffs = app
                                         .on_enter(event.modifiers.contains(KeyModifiers::CONTROL))?
                                 }
                             }
-                            KeyCode::Backspace => {
-                                if app.active_view != ActiveView::Models {
-                                    reload_diffs = app.on_backspace()?
-                                }
+                            KeyCode::Backspace if app.active_view != ActiveView::Models => {
+                                reload_diffs = app.on_backspace()?
                             }
                             _ => {}
                         }
@@ -1023,6 +1602,30 @@ fn run(
                             }
                             needs_redraw = true;
                         }
+                        while let Ok(response) = history_response_rx.try_recv() {
+                            app.handle_history_response(response);
+                            needs_redraw = true;
+                        }
+                        while let Ok(response) = analytics_response_rx.try_recv() {
+                            app.handle_analytics_response(response);
+                            needs_redraw = true;
+                        }
+                        while let Ok(response) = lint_response_rx.try_recv() {
+                            app.handle_lint_response(response);
+                            needs_redraw = true;
+                        }
+                        while let Ok(response) = pipeline_info_response_rx.try_recv() {
+                            app.handle_pipeline_info_response(response);
+                            needs_redraw = true;
+                        }
+                        while let Ok(response) = compare_response_rx.try_recv() {
+                            app.handle_compare_response(response);
+                            needs_redraw = true;
+                        }
+                        while let Ok(response) = merge_request_response_rx.try_recv() {
+                            app.handle_merge_request_pipelines_response(response);
+                            needs_redraw = true;
+                        }
                         while let Ok(response) = job_log_response_rx.try_recv() {
                             app.handle_job_log_response(response);
                             if app.pipeline_state.selected_job_is_running() {
@@ -1202,6 +1805,7 @@ fn run(
                                                     max_commits,
                                                 )?;
                                                 new_app.pipeline_load_limit = pipeline_load_limit;
+                                                new_app.pipeline_policy = pipeline_policy.clone();
                                                 new_app.set_pipeline_channel(
                                                     pipeline_request_tx.clone(),
                                                 );
@@ -1211,6 +1815,22 @@ fn run(
                                                 new_app.set_job_log_channel(
                                                     job_log_request_tx.clone(),
                                                 );
+                                                new_app.set_history_channel(
+                                                    history_request_tx.clone(),
+                                                );
+                                                new_app.set_analytics_channel(
+                                                    analytics_request_tx.clone(),
+                                                );
+                                                new_app.set_lint_channel(lint_request_tx.clone());
+                                                new_app.set_pipeline_info_channel(
+                                                    pipeline_info_request_tx.clone(),
+                                                );
+                                                new_app.set_compare_channel(
+                                                    compare_request_tx.clone(),
+                                                );
+                                                new_app.set_merge_request_channel(
+                                                    merge_request_tx.clone(),
+                                                );
                                                 new_app.request_batch_pipelines();
                                                 app = Some(new_app);
                                             }
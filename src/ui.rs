@@ -1,20 +1,34 @@
 use crate::app::{ActiveView, App, DiffMode};
+use crate::bisect::Bisect;
 use crate::dialogs::FileDialog;
+use crate::gitlab::compare::JobChange;
+use crate::gitlab::models::Job;
+use crate::gitlab::problems::Severity;
 use crate::gitlab_config::GitLabConfigDialog;
 use crate::theme;
 use crate::util::syntax_highlight::as_styled;
+use crate::widgets::analytics_view::AnalyticsViewState;
+use crate::widgets::branches_view::BranchSort;
 use crate::widgets::branches_view::{BranchList, BranchListItem};
 use crate::widgets::commit_view::CommitView;
+use crate::widgets::compare_view::CompareViewState;
 use crate::widgets::files_view::{FileList, FileListItem};
-use crate::widgets::graph_view::GraphView;
+use crate::widgets::graph_view::{pipeline_status_to_color, GraphView};
+use crate::widgets::history_view::HistoryViewState;
+use crate::widgets::lint_view::LintViewState;
 use crate::widgets::models_view::ModelListState;
-use crate::widgets::pipeline_view::PipelineView;
+use crate::widgets::pipeline_view::{
+    format_duration, status_color, LogSearch, PipelineView, PipelineViewState,
+};
+use crate::widgets::problems_view::ProblemsViewState;
+use crate::widgets::remotes_view::RemoteListState;
 use lazy_static::lazy_static;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{
-    Block, BorderType, Borders, Clear, List, ListItem as TuiListItem, Paragraph, Wrap,
+    Bar, BarChart, BarGroup, Block, BorderType, Borders, Clear, List, ListItem as TuiListItem,
+    Paragraph, Row, Table, Wrap,
 };
 use ratatui::Frame;
 
@@ -95,6 +109,90 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         return;
     }
 
+    if let (ActiveView::Remotes, Some(remote_state)) = (&app.active_view, &mut app.remotes_state) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(f.area());
+
+        let help = Paragraph::new("  Enter = use for CI, Esc = abort.");
+        f.render_widget(help, chunks[0]);
+
+        draw_remotes(f, chunks[1], app.color, remote_state);
+        return;
+    }
+
+    if let (ActiveView::History, Some(history_state)) = (&app.active_view, &mut app.history_state) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(f.area());
+
+        let help = Paragraph::new("  Enter = show commit and pipeline, Esc = back.");
+        f.render_widget(help, chunks[0]);
+
+        draw_history(f, chunks[1], app.color, history_state);
+        return;
+    }
+
+    if let (ActiveView::Analytics, Some(analytics_state)) =
+        (&app.active_view, &mut app.analytics_state)
+    {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(f.area());
+
+        let help = Paragraph::new("  Flaky = failed and succeeded on the same commit. Esc = back.");
+        f.render_widget(help, chunks[0]);
+
+        draw_analytics(f, chunks[1], app.color, analytics_state);
+        return;
+    }
+
+    if let (ActiveView::Problems, Some(problems_state)) =
+        (&app.active_view, &mut app.problems_state)
+    {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(f.area());
+
+        let help = Paragraph::new("  Enter = open file at line, Esc = back.");
+        f.render_widget(help, chunks[0]);
+
+        draw_problems(f, chunks[1], app.color, problems_state);
+        return;
+    }
+
+    if let (ActiveView::Lint, Some(lint_state)) = (&app.active_view, &mut app.lint_state) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(f.area());
+
+        let help = Paragraph::new("  Up/Down = scroll, Esc = back.");
+        f.render_widget(help, chunks[0]);
+
+        draw_lint(f, chunks[1], app.color, lint_state);
+        return;
+    }
+
+    if let (ActiveView::Compare, Some(compare_state)) = (&app.active_view, &mut app.compare_state) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(f.area());
+
+        let help = Paragraph::new(
+            "  Older commit on the left. Slower = 50% and at least 30s longer. Esc = back.",
+        );
+        f.render_widget(help, chunks[0]);
+
+        draw_compare(f, chunks[1], app.color, compare_state);
+        return;
+    }
+
     if app.is_fullscreen {
         let view = if app.active_view == ActiveView::Search {
             app.prev_active_view.as_ref().unwrap_or(&ActiveView::Graph)
@@ -197,6 +295,10 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         (&app.active_view, &app.gitlab_config_dialog)
     {
         draw_gitlab_config_dialog(f, f.area(), dialog, app.color);
+    } else if app.active_view == ActiveView::Pipeline && app.pipeline_state.job_details_open {
+        if let Some(job) = app.pipeline_state.selected_job() {
+            draw_job_details(f, f.area(), job, app.color);
+        }
     }
 
     if let ActiveView::Logo = app.active_view {
@@ -215,8 +317,26 @@ fn create_title<'a>(title: &'a str, hint: &'a str, color: bool) -> Line<'a> {
     ])
 }
 
+fn bisect_summary(bisect: &Bisect) -> String {
+    if let Some(oid) = bisect.first_bad() {
+        return format!("first bad {}", &oid.to_string()[..7]);
+    }
+    match (bisect.bad, bisect.good.is_empty(), bisect.remaining()) {
+        (None, _, _) => "mark a bad commit".to_string(),
+        (_, true, _) => "mark a good commit".to_string(),
+        (_, _, 0) => "bad commit is an ancestor of a good one".to_string(),
+        (_, _, remaining) => format!("{} left, ~{} steps", remaining, bisect.steps_left()),
+    }
+}
+
 fn draw_graph(f: &mut Frame, target: Rect, app: &mut App) {
-    let title = format!("Graph - {}", app.repo_name);
+    let mut title = format!("Graph - {}", app.repo_name);
+    if let Some(filter) = app.graph_state.status_filter {
+        title.push_str(&format!(" [{}]", filter.label()));
+    }
+    if let Some(bisect) = &app.graph_state.bisect {
+        title.push_str(&format!(" [bisect: {}]", bisect_summary(bisect)));
+    }
     let mut block = Block::default().borders(Borders::ALL).title(create_title(
         &title,
         " <-Branches | Commit-> ",
@@ -239,29 +359,63 @@ fn draw_graph(f: &mut Frame, target: Rect, app: &mut App) {
 fn draw_branches(f: &mut Frame, target: Rect, app: &mut App) {
     let color = app.color;
 
-    let mut block = Block::default().borders(Borders::ALL).title(create_title(
-        "Branches",
-        " Graph-> ",
-        app.color,
-    ));
+    let mut arrangement = vec![];
+    if let Some(filter) = app.branch_filter {
+        arrangement.push(filter.label());
+    }
+    if app.branch_sort == BranchSort::Status {
+        arrangement.push("by status");
+    }
+    let title = if arrangement.is_empty() {
+        "Branches".to_string()
+    } else {
+        format!("Branches [{}]", arrangement.join(", "))
+    };
+    let mut block =
+        Block::default()
+            .borders(Borders::ALL)
+            .title(create_title(&title, " Graph-> ", app.color));
 
-    if let Some(state) = &mut app.graph_state.branches {
+    let show_badges = app.remote_info.as_ref().is_some_and(|r| r.is_valid());
+    if let (Some(state), Some(graph)) = (&mut app.graph_state.branches, &app.graph_state.graph) {
         if app.active_view == ActiveView::Branches {
             block = block.border_type(BorderType::Thick);
         }
 
+        let watched = &app.watched_branches;
+        let statuses = &app.graph_state.pipeline_statuses;
+        let tick = app.graph_state.animation_tick;
         let items: Vec<_> = state
             .items
             .iter()
             .map(|item| {
-                BranchListItem::new(
+                let name = if item.index.is_some() && watched.contains(&item.name) {
+                    format!("{} \u{25c9}", item.name)
+                } else {
+                    item.name.clone()
+                };
+                let list_item = BranchListItem::new(
                     if color {
-                        Span::styled(&item.name, Style::default().fg(Color::Indexed(item.color)))
+                        Span::styled(name, Style::default().fg(Color::Indexed(item.color)))
                     } else {
-                        Span::raw(&item.name)
+                        Span::raw(name)
                     },
                     &item.branch_type,
-                )
+                );
+                match item.index {
+                    Some(index) if show_badges => {
+                        let target = graph.all_branches[index].target.to_string();
+                        list_item.badge(match statuses.get(&target) {
+                            Some(status) if color => Span::styled(
+                                status.animated_symbol(tick),
+                                Style::default().fg(pipeline_status_to_color(*status)),
+                            ),
+                            Some(status) => Span::raw(status.animated_symbol(tick)),
+                            None => Span::raw(" "),
+                        })
+                    }
+                    _ => list_item,
+                }
             })
             .collect();
 
@@ -449,6 +603,7 @@ fn draw_diff(f: &mut Frame, target: Rect, app: &mut App) {
             if !state.diffs.is_empty() {
                 text.extend(style_diff_line(None, &state.diffs[0].0, &styles, false));
             }
+            let header_lines = text.lines.len();
             if !state.diffs.len() > 1 {
                 if let Some(txt) = &state.highlighted {
                     text.extend(as_styled(txt));
@@ -468,6 +623,12 @@ fn draw_diff(f: &mut Frame, target: Rect, app: &mut App) {
                     }
                 }
             }
+            if let Some(marked) = state.marked_line {
+                let index = header_lines + marked.saturating_sub(1) as usize;
+                if let Some(line) = text.lines.get_mut(index) {
+                    line.style = line.style.bg(theme::BG_HIGHLIGHT);
+                }
+            }
         }
 
         let mut paragraph = Paragraph::new(text).block(block).scroll(state.scroll);
@@ -537,29 +698,204 @@ fn draw_pipeline(f: &mut Frame, target: Rect, app: &mut App) {
             .split(target)
     };
 
-    let mut block = Block::default().borders(Borders::ALL).title(create_title(
-        "Pipeline",
-        " P=toggle L=log ",
-        app.color,
-    ));
+    let is_preview = app
+        .pipeline_state
+        .details
+        .as_ref()
+        .is_some_and(|d| d.preview);
+    let (title, hint) = if is_preview {
+        ("Pipeline preview (.gitlab-ci.yml)", " P=toggle ")
+    } else if app.pipeline_state.include_retried {
+        (
+            "Pipeline (with retried jobs)",
+            " P=toggle L=log T=timeline ",
+        )
+    } else {
+        ("Pipeline", " P=toggle L=log T=timeline ")
+    };
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(create_title(title, hint, app.color));
 
     if app.active_view == ActiveView::Pipeline && !app.pipeline_state.job_log_focused {
         block = block.border_type(BorderType::Thick);
     }
 
-    let mut pipeline = PipelineView::default().block(block);
+    let inner = block.inner(chunks[0]);
+    f.render_widget(block, chunks[0]);
+
+    let header = pipeline_header(&app.pipeline_state, app.color);
+    let header_height = (header.len() as u16).min(inner.height / 2);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(header_height), Constraint::Min(0)])
+        .split(inner);
+    if header_height > 0 {
+        f.render_widget(Paragraph::new(header), parts[0]);
+    }
+
+    let mut pipeline = PipelineView::default();
 
     if app.color {
         pipeline = pipeline.highlight_style(Style::default().add_modifier(Modifier::BOLD));
     }
 
-    f.render_stateful_widget(pipeline, chunks[0], &mut app.pipeline_state);
+    f.render_stateful_widget(pipeline, parts[1], &mut app.pipeline_state);
 
     if has_log && chunks.len() > 1 {
         draw_job_log(f, chunks[1], app);
     }
 }
 
+/// Lines above the stages describing the shown pipeline: how and by whom it was
+/// triggered, its timings, configuration errors and optionally its variables.
+fn pipeline_header(state: &PipelineViewState, color: bool) -> Vec<Line<'static>> {
+    const MAX_VARIABLES: usize = 8;
+
+    if state.error.is_some() {
+        return vec![];
+    }
+    let pipeline = match state.info() {
+        Some(pipeline) => pipeline,
+        None => return vec![],
+    };
+    let dim = if color {
+        Style::default().fg(theme::TEXT_DIM)
+    } else {
+        Style::default()
+    };
+    let time = |t: &Option<String>| {
+        t.as_deref()
+            .and_then(|t| t.get(..19))
+            .map(|t| t.replace('T', " "))
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    let status = format!("{} {}", pipeline.status.symbol(), pipeline.status);
+    let mut first = vec![
+        Span::styled(format!("#{} ", pipeline.id), dim),
+        if color {
+            Span::styled(status, Style::default().fg(status_color(pipeline.status)))
+        } else {
+            Span::raw(status)
+        },
+    ];
+    if let Some(source) = &pipeline.source {
+        first.push(Span::styled("  source ", dim));
+        first.push(Span::raw(source.replace('_', " ")));
+    }
+    if let Some(user) = &pipeline.user {
+        first.push(Span::styled("  by ", dim));
+        first.push(Span::raw(user.username.clone()));
+    }
+    if let Some(ref_name) = &pipeline.ref_name {
+        first.push(Span::styled("  on ", dim));
+        first.push(Span::raw(ref_name.clone()));
+        if pipeline.tag.unwrap_or(false) {
+            first.push(Span::styled(" (tag)", dim));
+        }
+    }
+    if pipeline.is_merged_result() && state.current_sha.as_ref() != Some(&pipeline.sha) {
+        first.push(Span::styled("  merged result ", dim));
+        first.push(Span::raw(pipeline.sha.chars().take(8).collect::<String>()));
+    }
+
+    let mut second = vec![];
+    let mut field = |label: &str, value: String| {
+        if !second.is_empty() {
+            second.push(Span::raw("  "));
+        }
+        second.push(Span::styled(format!("{} ", label), dim));
+        second.push(Span::raw(value));
+    };
+    field("created", time(&pipeline.created_at));
+    if let Some(queued) = pipeline.queued_duration {
+        field("queued", format_duration(queued.round() as u64));
+    }
+    field("started", time(&pipeline.started_at));
+    field("finished", time(&pipeline.finished_at));
+    if let Some(duration) = pipeline.duration {
+        field("duration", format_duration(duration));
+    }
+
+    let mut lines = vec![];
+    let pipelines = state
+        .details
+        .as_ref()
+        .map_or(&[][..], |d| d.pipelines.as_slice());
+    if pipelines.len() > 1 {
+        // Oldest first, so that `<` moves left to older pipelines
+        let mut switcher = vec![Span::styled("< ", dim)];
+        for (idx, other) in pipelines.iter().rev().enumerate() {
+            if idx > 0 {
+                switcher.push(Span::styled(" | ", dim));
+            }
+            let text = format!(
+                "{} #{} {}",
+                other.status.symbol(),
+                other.id,
+                other
+                    .source
+                    .as_deref()
+                    .unwrap_or("unknown")
+                    .replace('_', " ")
+            );
+            let mut style = if color {
+                Style::default().fg(status_color(other.status))
+            } else {
+                Style::default()
+            };
+            if other.id == pipeline.id {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            switcher.push(Span::styled(text, style));
+        }
+        switcher.push(Span::styled(" >", dim));
+        lines.push(Line::from(switcher));
+    }
+    lines.push(Line::from(first));
+    lines.push(Line::from(second));
+    if let Some(errors) = &pipeline.yaml_errors {
+        let style = if color {
+            Style::default().fg(theme::ERROR)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::styled(format!("YAML errors: {}", errors), style));
+    }
+
+    if state.show_variables {
+        match state.variables() {
+            None => lines.push(Line::styled("Variables: loading...", dim)),
+            Some(Err(e)) => lines.push(Line::styled(format!("Variables: {}", e), dim)),
+            Some(Ok(variables)) if variables.is_empty() => {
+                lines.push(Line::styled("Variables: none", dim))
+            }
+            Some(Ok(variables)) => {
+                lines.push(Line::styled("Variables:", dim));
+                for variable in variables.iter().take(MAX_VARIABLES) {
+                    let mut spans = vec![
+                        Span::raw(format!("  {}", variable.key)),
+                        Span::styled(" = ", dim),
+                        Span::raw(variable.value.clone()),
+                    ];
+                    if variable.variable_type.as_deref() == Some("file") {
+                        spans.push(Span::styled(" (file)", dim));
+                    }
+                    lines.push(Line::from(spans));
+                }
+                if variables.len() > MAX_VARIABLES {
+                    lines.push(Line::styled(
+                        format!("  ... {} more", variables.len() - MAX_VARIABLES),
+                        dim,
+                    ));
+                }
+            }
+        }
+    }
+    lines
+}
+
 fn draw_job_log(f: &mut Frame, target: Rect, app: &mut App) {
     let job_name = app
         .pipeline_state
@@ -571,9 +907,13 @@ fn draw_job_log(f: &mut Frame, target: Rect, app: &mut App) {
         .unwrap_or_else(|| "Job Log".to_string());
 
     let title = format!(" {} ", job_name);
+    let hint = match &app.pipeline_state.job_log_notice {
+        Some(notice) => format!(" {} ", notice),
+        None => " C=copy | /=search | Z=fold | V=pager ".to_string(),
+    };
     let mut block = Block::default()
         .borders(Borders::ALL)
-        .title(create_title(&title, " C=copy ", app.color));
+        .title(create_title(&title, &hint, app.color));
 
     if app.active_view == ActiveView::Pipeline && app.pipeline_state.job_log_focused {
         block = block.border_type(BorderType::Thick);
@@ -606,23 +946,40 @@ fn draw_job_log(f: &mut Frame, target: Rect, app: &mut App) {
     let dim_style = Style::default().fg(theme::TEXT_DIM);
     let duration_style = Style::default().fg(theme::BORDER);
 
+    let inner = if app.pipeline_state.log_search.is_active() && inner.height > 1 {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+            .split(inner);
+        draw_log_search_bar(f, chunks[1], &app.pipeline_state.log_search, app.color);
+        chunks[0]
+    } else {
+        inner
+    };
+
     let visible_lines = inner.height as usize;
     app.pipeline_state.job_log_visible_height = inner.height;
+    let search = &app.pipeline_state.log_search;
 
-    let max_scroll = total_lines.saturating_sub(visible_lines) as u16;
+    let max_scroll = app
+        .pipeline_state
+        .job_log_visible
+        .len()
+        .saturating_sub(visible_lines) as u16;
     if app.pipeline_state.job_log_scroll > max_scroll {
         app.pipeline_state.job_log_scroll = max_scroll;
     }
     let scroll = app.pipeline_state.job_log_scroll as usize;
+    let sections = &app.pipeline_state.job_log_sections;
 
     let lines: Vec<Line> = app
         .pipeline_state
-        .job_log
+        .job_log_visible
         .iter()
-        .enumerate()
         .skip(scroll)
         .take(visible_lines)
-        .map(|(idx, log_line)| {
+        .map(|&idx| {
+            let log_line = &app.pipeline_state.job_log[idx];
             let line_num = format!("{:>width$}", idx + 1, width = line_num_width as usize);
             let ts = log_line.timestamp.as_deref().unwrap_or("        ");
 
@@ -640,23 +997,59 @@ fn draw_job_log(f: &mut Frame, target: Rect, app: &mut App) {
                 .unwrap_or(0);
             let max_content: u16 = inner.width.saturating_sub(gutter_width + dur_reserve);
 
+            let (ranges, current) = search.line_matches(idx);
+            let segments = if ranges.is_empty() {
+                std::borrow::Cow::Borrowed(&log_line.styled)
+            } else {
+                std::borrow::Cow::Owned(highlight_matches(&log_line.styled, &ranges, current))
+            };
+
             let mut content_len: u16 = 0;
-            for (text, style) in &log_line.styled {
+            let section = log_line.section.map(|s| &sections[s]);
+            if let Some(section) = section {
+                let marker = format!(
+                    "{}{} ",
+                    "  ".repeat(log_line.depth),
+                    if section.collapsed {
+                        "\u{25b8}"
+                    } else {
+                        "\u{25be}"
+                    }
+                );
+                content_len += marker.chars().count() as u16;
+                spans.push(Span::styled(marker, Style::default().fg(theme::ACCENT)));
+            }
+            let header_style = Style::default().add_modifier(Modifier::BOLD);
+
+            for (text, style) in segments.iter() {
+                let style = if section.is_some() {
+                    style.patch(header_style)
+                } else {
+                    *style
+                };
                 let remaining = max_content.saturating_sub(content_len) as usize;
                 if remaining == 0 {
                     break;
                 }
                 if text.len() <= remaining {
-                    spans.push(Span::styled(text.clone(), *style));
+                    spans.push(Span::styled(text.clone(), style));
                     content_len += text.len() as u16;
                 } else {
                     let truncated: String = text.chars().take(remaining).collect();
                     content_len += truncated.len() as u16;
-                    spans.push(Span::styled(truncated, *style));
+                    spans.push(Span::styled(truncated, style));
                     break;
                 }
             }
 
+            if let Some(section) = section.filter(|s| s.collapsed) {
+                let hidden = format!(" ({} lines)", section.end - section.header - 1);
+                if content_len + hidden.len() as u16 <= max_content {
+                    content_len += hidden.len() as u16;
+                    spans.push(Span::styled(hidden, dim_style));
+                }
+            }
+
             if let Some(dur) = &log_line.duration {
                 let used = gutter_width + content_len + dur.len() as u16 + 1;
                 let pad = inner.width.saturating_sub(used);
@@ -680,6 +1073,93 @@ fn draw_job_log(f: &mut Frame, target: Rect, app: &mut App) {
     f.render_widget(paragraph, inner);
 }
 
+fn draw_log_search_bar(f: &mut Frame, target: Rect, search: &LogSearch, color: bool) {
+    let cursor = if search.editing { "_" } else { "" };
+    let mode = if search.regex { "regex" } else { "text" };
+    let status = if let Some(err) = &search.error {
+        err.clone()
+    } else if search.query.is_empty() {
+        String::new()
+    } else if search.matches.is_empty() {
+        "no matches".to_string()
+    } else {
+        format!(
+            "{}/{}",
+            search.current.map(|c| c + 1).unwrap_or(0),
+            search.matches.len()
+        )
+    };
+    let hint = if search.editing {
+        "Tab=regex, Enter=done, Esc=clear"
+    } else {
+        "N/Shift+N=next/prev"
+    };
+
+    let line = if color {
+        let status_style = if search.error.is_some() || search.matches.is_empty() {
+            Style::default().fg(theme::ERROR)
+        } else {
+            Style::default().fg(theme::ACCENT)
+        };
+        Line::from(vec![
+            Span::styled("/", Style::default().fg(theme::ACCENT)),
+            Span::raw(format!("{}{}", search.query, cursor)),
+            Span::styled(
+                format!("  [{}] ", mode),
+                Style::default().fg(theme::TEXT_DIM),
+            ),
+            Span::styled(status, status_style),
+            Span::styled(format!("  {}", hint), Style::default().fg(theme::TEXT_DIM)),
+        ])
+    } else {
+        Line::from(format!(
+            "/{}{}  [{}] {}  {}",
+            search.query, cursor, mode, status, hint
+        ))
+    };
+    f.render_widget(Paragraph::new(line), target);
+}
+
+/// Splits styled segments at match boundaries and highlights the matched parts.
+/// `ranges` are byte ranges into the concatenated segment text.
+fn highlight_matches(
+    segments: &[(String, Style)],
+    ranges: &[std::ops::Range<usize>],
+    current: Option<usize>,
+) -> Vec<(String, Style)> {
+    let match_style = Style::default().fg(theme::BG).bg(theme::WARNING);
+    let current_style = Style::default().fg(theme::BG).bg(theme::ACCENT);
+
+    let mut result = vec![];
+    let mut offset = 0;
+    for (text, style) in segments {
+        let seg_end = offset + text.len();
+        let mut pos = offset;
+        for (i, range) in ranges.iter().enumerate() {
+            if range.end <= pos || range.start >= seg_end {
+                continue;
+            }
+            let start = range.start.max(pos);
+            let end = range.end.min(seg_end);
+            if start > pos {
+                result.push((text[pos - offset..start - offset].to_string(), *style));
+            }
+            let hl = if current == Some(i) {
+                current_style
+            } else {
+                match_style
+            };
+            result.push((text[start - offset..end - offset].to_string(), hl));
+            pos = end;
+        }
+        if pos < seg_end {
+            result.push((text[pos - offset..].to_string(), *style));
+        }
+        offset = seg_end;
+    }
+    result
+}
+
 fn draw_models(f: &mut Frame, target: Rect, color: bool, state: &mut ModelListState) {
     let block = Block::default()
         .borders(Borders::ALL)
@@ -700,6 +1180,490 @@ fn draw_models(f: &mut Frame, target: Rect, color: bool, state: &mut ModelListSt
     f.render_stateful_widget(list, target, &mut state.state);
 }
 
+fn draw_remotes(f: &mut Frame, target: Rect, color: bool, state: &mut RemoteListState) {
+    let block = Block::default().borders(Borders::ALL).title(" CI remote ");
+
+    let name_width = state
+        .remotes
+        .iter()
+        .map(|r| r.name.as_deref().unwrap_or("").len())
+        .max()
+        .unwrap_or(0);
+
+    let items: Vec<_> = state
+        .remotes
+        .iter()
+        .map(|r| {
+            let name = format!(
+                "{:<width$}",
+                r.name.as_deref().unwrap_or(""),
+                width = name_width
+            );
+            let target = if r.is_valid() {
+                format!(
+                    "{}  {}",
+                    r.host.as_deref().unwrap_or(""),
+                    r.project_id.as_deref().unwrap_or("")
+                )
+            } else {
+                "(no GitLab project)".to_string()
+            };
+            if color {
+                let target_style = if r.is_valid() {
+                    Style::default().fg(theme::ACCENT)
+                } else {
+                    Style::default().fg(theme::TEXT_DIM)
+                };
+                TuiListItem::new(Line::from(vec![
+                    Span::raw(name),
+                    Span::raw("  "),
+                    Span::styled(target, target_style),
+                ]))
+            } else {
+                TuiListItem::new(format!("{}  {}", name, target))
+            }
+        })
+        .collect();
+
+    let mut list = List::new(items).block(block).highlight_symbol("> ");
+
+    if color {
+        list = list.highlight_style(Style::default().add_modifier(Modifier::UNDERLINED));
+    }
+
+    f.render_stateful_widget(list, target, &mut state.state);
+}
+
+fn draw_history(f: &mut Frame, target: Rect, color: bool, state: &mut HistoryViewState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(12), Constraint::Min(0)].as_ref())
+        .split(target);
+
+    let chart_block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Durations - {} ", state.ref_name));
+    let list_block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Pipelines - {} ", state.ref_name));
+
+    if state.loading || state.error.is_some() || state.pipelines.is_empty() {
+        let text = if state.loading {
+            "Loading...".to_string()
+        } else if let Some(err) = &state.error {
+            err.clone()
+        } else {
+            "No pipelines for this branch".to_string()
+        };
+        f.render_widget(chart_block, chunks[0]);
+        f.render_widget(
+            Paragraph::new(text)
+                .block(list_block)
+                .wrap(Wrap { trim: true }),
+            chunks[1],
+        );
+        return;
+    }
+
+    // Oldest pipeline on the left
+    let inner_width = chunks[0].width.saturating_sub(2) as usize;
+    let count = state.pipelines.len().min((inner_width / 2).max(1));
+    let bar_width = ((inner_width / count).saturating_sub(1)).clamp(1, 8) as u16;
+    let bars: Vec<_> = state
+        .pipelines
+        .iter()
+        .take(count)
+        .rev()
+        .map(|p| {
+            let duration = p.duration.unwrap_or(0);
+            let mut bar = Bar::default()
+                .value(duration)
+                .text_value(if bar_width >= 5 {
+                    format_duration(duration)
+                } else {
+                    String::new()
+                });
+            if color {
+                bar = bar.style(Style::default().fg(status_color(p.status)));
+            }
+            bar
+        })
+        .collect();
+    let chart = BarChart::default()
+        .block(chart_block)
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_gap(1);
+    f.render_widget(chart, chunks[0]);
+
+    let items: Vec<_> = state
+        .pipelines
+        .iter()
+        .map(|p| {
+            let id = format!("#{:<7}", p.iid.unwrap_or(p.id));
+            let status = format!("{} {:<9}", p.status.symbol(), p.status.to_string());
+            let duration = p
+                .duration
+                .map(format_duration)
+                .unwrap_or_else(|| "--:--".to_string());
+            let created = p
+                .created_at
+                .as_deref()
+                .and_then(|c| c.get(..16))
+                .unwrap_or("")
+                .replace('T', " ");
+            let user = p
+                .user
+                .as_ref()
+                .map(|u| format!("@{}", u.username))
+                .unwrap_or_default();
+            let details = format!(
+                "{}  {}  {:<16} {:<20} {}",
+                duration,
+                created,
+                user,
+                p.source.as_deref().unwrap_or(""),
+                &p.sha[..p.sha.len().min(7)]
+            );
+            if color {
+                TuiListItem::new(Line::from(vec![
+                    Span::raw(id),
+                    Span::raw(" "),
+                    Span::styled(status, Style::default().fg(status_color(p.status))),
+                    Span::raw(" "),
+                    Span::raw(details),
+                ]))
+            } else {
+                TuiListItem::new(format!("{} {} {}", id, status, details))
+            }
+        })
+        .collect();
+
+    let mut list = List::new(items).block(list_block).highlight_symbol("> ");
+
+    if color {
+        list = list.highlight_style(Style::default().add_modifier(Modifier::UNDERLINED));
+    }
+
+    f.render_stateful_widget(list, chunks[1], &mut state.state);
+}
+
+fn draw_analytics(f: &mut Frame, target: Rect, color: bool, state: &mut AnalyticsViewState) {
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        " Job analytics - {} ({} pipelines) ",
+        state.ref_name, state.analytics.pipelines
+    ));
+
+    if state.loading || state.error.is_some() || state.analytics.jobs.is_empty() {
+        let text = if state.loading {
+            "Loading...".to_string()
+        } else if let Some(err) = &state.error {
+            err.clone()
+        } else {
+            "No finished jobs for this branch".to_string()
+        };
+        f.render_widget(
+            Paragraph::new(text).block(block).wrap(Wrap { trim: true }),
+            target,
+        );
+        return;
+    }
+
+    let format_secs = |d: Option<f64>| {
+        d.map(|d| format_duration(d.round() as u64))
+            .unwrap_or_else(|| "--:--".to_string())
+    };
+
+    let rows: Vec<_> = state
+        .analytics
+        .jobs
+        .iter()
+        .map(|job| {
+            let fail_rate = 100.0 * job.failures as f64 / job.runs as f64;
+            let flaky = if job.is_flaky() {
+                format!("flaky ({})", job.flaky_shas)
+            } else {
+                String::new()
+            };
+            let name = if job.allow_failure {
+                format!("{} (allowed to fail)", job.name)
+            } else {
+                job.name.clone()
+            };
+            let row = Row::new(vec![
+                name,
+                job.stage.clone(),
+                job.runs.to_string(),
+                format!("{:.0}%", fail_rate),
+                format_secs(job.p50),
+                format_secs(job.p95),
+                flaky,
+            ]);
+            if color && job.is_flaky() {
+                row.style(Style::default().fg(theme::WARNING))
+            } else if color && job.failures > 0 && !job.allow_failure {
+                row.style(Style::default().fg(theme::ERROR))
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let header =
+        Row::new(vec!["Job", "Stage", "Runs", "Failed", "p50", "p95", ""]).style(if color {
+            Style::default().fg(theme::ACCENT)
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        });
+
+    let mut table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(16),
+            Constraint::Length(5),
+            Constraint::Length(7),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+    .block(block)
+    .highlight_symbol("> ");
+
+    if color {
+        table = table.row_highlight_style(Style::default().add_modifier(Modifier::UNDERLINED));
+    }
+
+    f.render_stateful_widget(table, target, &mut state.state);
+}
+
+fn draw_problems(f: &mut Frame, target: Rect, color: bool, state: &mut ProblemsViewState) {
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        " Problems - {} ({}) ",
+        state.job_name,
+        state.problems.len()
+    ));
+
+    if state.problems.is_empty() {
+        f.render_widget(
+            Paragraph::new("No source locations found in the job log").block(block),
+            target,
+        );
+        return;
+    }
+
+    let items: Vec<_> = state
+        .problems
+        .iter()
+        .map(|p| {
+            let location = match p.column {
+                Some(col) => format!("{}:{}:{}", p.file, p.line, col),
+                None => format!("{}:{}", p.file, p.line),
+            };
+            let tag = format!("[{}]", p.matcher);
+            if color {
+                let style = match p.severity {
+                    Severity::Error => Style::default().fg(theme::ERROR),
+                    Severity::Warning => Style::default().fg(theme::WARNING),
+                };
+                TuiListItem::new(Line::from(vec![
+                    Span::styled(location, style),
+                    Span::raw("  "),
+                    Span::raw(p.message.clone()),
+                    Span::raw("  "),
+                    Span::styled(tag, Style::default().fg(theme::TEXT_DIM)),
+                ]))
+            } else {
+                TuiListItem::new(format!("{}  {}  {}", location, p.message, tag))
+            }
+        })
+        .collect();
+
+    let mut list = List::new(items).block(block).highlight_symbol("> ");
+
+    if color {
+        list = list.highlight_style(Style::default().add_modifier(Modifier::UNDERLINED));
+    }
+
+    f.render_stateful_widget(list, target, &mut state.state);
+}
+
+fn draw_compare(f: &mut Frame, target: Rect, color: bool, state: &mut CompareViewState) {
+    let describe = |sha: &str, pipeline: Option<u64>| match pipeline {
+        Some(id) => format!("#{} ({})", id, &sha[..7]),
+        None => sha[..7].to_string(),
+    };
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        " Pipeline comparison - {} -> {} ",
+        describe(&state.base_sha, state.base.as_ref().map(|p| p.id)),
+        describe(&state.head_sha, state.head.as_ref().map(|p| p.id)),
+    ));
+
+    if state.loading || state.error.is_some() || state.jobs.is_empty() {
+        let text = if state.loading {
+            "Loading...".to_string()
+        } else if let Some(err) = &state.error {
+            err.clone()
+        } else {
+            "Both pipelines have no jobs".to_string()
+        };
+        f.render_widget(
+            Paragraph::new(text).block(block).wrap(Wrap { trim: true }),
+            target,
+        );
+        return;
+    }
+
+    let run = |job: &Option<Job>| match job {
+        Some(job) => format!(
+            "{} {:<8} {}",
+            job.status.symbol(),
+            job.status.to_string(),
+            job.duration
+                .map(|d| format_duration(d.round() as u64))
+                .unwrap_or_else(|| "--:--".to_string())
+        ),
+        None => "-".to_string(),
+    };
+    let delta = |base: &Option<Job>, head: &Option<Job>| match (
+        base.as_ref().and_then(|j| j.duration),
+        head.as_ref().and_then(|j| j.duration),
+    ) {
+        (Some(old), Some(new)) => {
+            let diff = (new - old).round() as i64;
+            let sign = if diff < 0 { "-" } else { "+" };
+            format!("{}{}", sign, format_duration(diff.unsigned_abs()))
+        }
+        _ => String::new(),
+    };
+
+    let rows: Vec<_> = state
+        .jobs
+        .iter()
+        .map(|job| {
+            let row = Row::new(vec![
+                job.name.clone(),
+                job.stage.clone(),
+                run(&job.base),
+                run(&job.head),
+                delta(&job.base, &job.head),
+                job.change.label().to_string(),
+            ]);
+            let fg = match job.change {
+                JobChange::NewFailure => Some(theme::ERROR),
+                JobChange::Fixed => Some(theme::SUCCESS),
+                JobChange::Slower => Some(theme::WARNING),
+                JobChange::Added | JobChange::Removed => Some(theme::ACCENT_BLUE),
+                JobChange::Unchanged => None,
+            };
+            match fg {
+                Some(fg) if color => row.style(Style::default().fg(fg)),
+                _ => row,
+            }
+        })
+        .collect();
+
+    let header = Row::new(vec!["Job", "Stage", "Before", "After", "Change", ""]).style(if color {
+        Style::default().fg(theme::ACCENT)
+    } else {
+        Style::default().add_modifier(Modifier::BOLD)
+    });
+
+    let mut table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(16),
+            Constraint::Length(18),
+            Constraint::Length(18),
+            Constraint::Length(7),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .block(block)
+    .highlight_symbol("> ");
+
+    if color {
+        table = table.row_highlight_style(Style::default().add_modifier(Modifier::UNDERLINED));
+    }
+
+    f.render_stateful_widget(table, target, &mut state.state);
+}
+
+fn draw_lint(f: &mut Frame, target: Rect, color: bool, state: &mut LintViewState) {
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        " CI lint - {} ({}) ",
+        &state.sha[..state.sha.len().min(7)],
+        state.ref_name.as_deref().unwrap_or("no branch")
+    ));
+
+    let result = match &state.result {
+        Some(result) if !state.loading && state.error.is_none() => result,
+        _ => {
+            let text = if let Some(err) = &state.error {
+                err.clone()
+            } else {
+                "Loading...".to_string()
+            };
+            f.render_widget(
+                Paragraph::new(text).block(block).wrap(Wrap { trim: true }),
+                target,
+            );
+            return;
+        }
+    };
+
+    let style = |c: Color| {
+        if color {
+            Style::default().fg(c)
+        } else {
+            Style::default()
+        }
+    };
+
+    let mut text = Text::default();
+    text.lines.push(if result.valid {
+        Line::styled("Configuration is valid", style(theme::SUCCESS))
+    } else {
+        Line::styled("Configuration is invalid", style(theme::ERROR))
+    });
+    for error in &result.errors {
+        text.lines.push(Line::styled(
+            format!("error: {}", error),
+            style(theme::ERROR),
+        ));
+    }
+    for warning in &result.warnings {
+        text.lines.push(Line::styled(
+            format!("warning: {}", warning),
+            style(theme::WARNING),
+        ));
+    }
+
+    if let Some(yaml) = &result.merged_yaml {
+        text.lines.push(Line::default());
+        text.lines.push(Line::styled(
+            "Merged configuration:",
+            style(theme::TEXT_DIM),
+        ));
+        match &state.highlighted {
+            Some(highlighted) => text.extend(as_styled(highlighted)),
+            None => text.extend(yaml.lines().map(|l| Line::raw(l.trim_end()))),
+        }
+    }
+
+    let max_scroll = (text.lines.len() as u16).saturating_sub(target.height.saturating_sub(2));
+    state.scroll = state.scroll.min(max_scroll);
+
+    f.render_widget(
+        Paragraph::new(text).block(block).scroll((state.scroll, 0)),
+        target,
+    );
+}
+
 fn draw_help(f: &mut Frame, target: Rect, scroll: u16) {
     let block = Block::default()
         .borders(Borders::ALL)
@@ -713,6 +1677,13 @@ fn draw_help(f: &mut Frame, target: Rect, scroll: u16) {
            Q                  Quit\n  \
            Ctrl + O           Open repository\n  \
            M                  Set branching model\n  \
+           Shift + R          Select CI remote\n  \
+           Shift + H          Pipeline history of selected/current branch\n  \
+           Shift + A          Job duration analytics and flaky jobs of selected/current branch\n  \
+           =                  Compare pipelines of selected and secondary selected commit\n  \
+           Shift + S          Branches: sort by tip pipeline status\n  \
+           F                  Branches: filter failed/running/passed\n  \
+           Shift + Y          Lint .gitlab-ci.yml of selected commit via GitLab\n  \
          \n\
          Layout/panels\n  \
          \n  \
@@ -730,14 +1701,53 @@ fn draw_help(f: &mut Frame, target: Rect, scroll: u16) {
            Home/End           Navigate to HEAD/last\n  \
            Ctrl + Up/Down     Secondary selection (compare arbitrary commits)\n  \
            Backspace          Clear secondary selection\n  \
+           ] / [              Next older / newer commit with failed pipeline\n  \
+           } / {              Next older / newer commit with running pipeline\n  \
+           ) / (              Next older / newer commit with passed pipeline\n  \
+           *                  Graph: dim commits not failed/running/passed (cycles)\n  \
            Ctrl + Left/Right  Scroll horizontal\n  \
            Enter              Jump to selected branch/tag\n  \
+           W                  Watch selected branch (notify when its pipeline finishes)\n  \
+         \n\
+         Bisect (state shared with git bisect)\n  \
+         \n  \
+           Ctrl + G           Start bisection\n  \
+           G                  Mark selected commit good\n  \
+           Shift + B          Mark selected commit bad\n  \
+           Shift + G          End bisection\n  \
+         \n\
+         Clipboard (wl-copy/xclip/xsel/pbcopy/clip, OSC 52 over SSH)\n  \
+         \n  \
+           C                  Copy commit hash / branch name / file path / diff hunk\n  \
+           Shift + C          Copy commit message / whole diff\n  \
+           Shift + O          Open commit / file / branch MRs / job in the browser\n  \
+           Shift + U          Copy that URL\n  \
          \n\
          Search\n  \
          \n  \
            F3/Ctrl+F          Open search dialog\n  \
            F3                 Continue search\n  \
          \n\
+         Pipeline panel\n  \
+         \n  \
+           L                  Toggle job log focus\n  \
+           I                  Details of selected job (runner, queue time, failure reason)\n  \
+           < / >              Older/newer pipeline of the commit (branch, MR, tag, schedule, reruns)\n  \
+           Shift + I          Show/hide earlier attempts of retried jobs\n  \
+           T                  Toggle timeline (job bars on a time axis) / stage boxes\n  \
+           /                  Search in job log (Tab toggles regex)\n  \
+           N/Shift + N        Next/previous match in job log\n  \
+           E                  Jump to first error in job log\n  \
+           Shift + F          Problems (source locations of errors) in job log\n  \
+           Z                  Collapse/expand log section at the top of the job log\n  \
+           Shift + Z          Collapse/expand all log sections\n  \
+           C                  Copy job log (when focused) or job URL\n  \
+           Shift + C          Copy pipeline URL\n  \
+           X/Shift + X        Save job log (ANSI-stripped/raw trace) to the current directory\n  \
+           V                  Open job log in $PAGER (default: less -R)\n  \
+           Shift + V          Show/hide pipeline variables\n  \
+           Shift + E          Open job log in $VISUAL/$EDITOR\n  \
+         \n\
          Diffs panel\n  \
          \n  \
            +/-                Increase/decrease number of diff context lines\n  \
@@ -786,6 +1796,146 @@ fn draw_search_dialog(f: &mut Frame, target: Rect, search: &Option<String>) {
     f.render_widget(paragraph, area);
 }
 
+fn draw_job_details(f: &mut Frame, target: Rect, job: &Job, color: bool) {
+    let mut block = Block::default()
+        .title(format!(" Job #{} - close with Esc ", job.id))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick);
+    if color {
+        block = block.border_style(Style::default().fg(theme::ACCENT));
+    }
+
+    let label_style = if color {
+        Style::default().fg(theme::TEXT_DIM)
+    } else {
+        Style::default()
+    };
+    let mut lines: Vec<Line> = vec![];
+    let mut field = |label: &str, value: Span<'static>| {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<14}", label), label_style),
+            value,
+        ]));
+    };
+    let time = |t: &Option<String>| {
+        t.as_deref()
+            .and_then(|t| t.get(..19))
+            .map(|t| t.replace('T', " "))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let secs = |d: Option<f64>| {
+        d.map(|d| format_duration(d.round() as u64))
+            .unwrap_or_else(|| "--:--".to_string())
+    };
+
+    field("Name", Span::raw(job.name.clone()));
+    field("Stage", Span::raw(job.stage.clone()));
+    let mut status = format!("{} {}", job.status.symbol(), job.status);
+    if let Some(reason) = &job.failure_reason {
+        status.push_str(&format!(" ({})", reason.replace('_', " ")));
+    }
+    if job.retried.unwrap_or(false) {
+        status.push_str(", retried");
+    }
+    if job.allow_failure.unwrap_or(false) {
+        status.push_str(", allowed to fail");
+    }
+    field(
+        "Status",
+        if color {
+            Span::styled(status, Style::default().fg(status_color(job.status)))
+        } else {
+            Span::raw(status)
+        },
+    );
+    field(
+        "Runner",
+        Span::raw(match &job.runner {
+            Some(runner) => format!(
+                "#{} {}{}",
+                runner.id,
+                runner.description.as_deref().unwrap_or(""),
+                match (&runner.runner_type, runner.is_shared) {
+                    (Some(kind), _) => format!(" ({})", kind.replace('_', " ")),
+                    (None, Some(true)) => " (shared)".to_string(),
+                    _ => String::new(),
+                }
+            ),
+            None => "-".to_string(),
+        }),
+    );
+    if let Some(runner) = job.runner.as_ref().filter(|r| !r.tag_list.is_empty()) {
+        field("Runner tags", Span::raw(runner.tag_list.join(", ")));
+    }
+    field(
+        "Job tags",
+        Span::raw(if job.tag_list.is_empty() {
+            "-".to_string()
+        } else {
+            job.tag_list.join(", ")
+        }),
+    );
+    field(
+        "User",
+        Span::raw(match &job.user {
+            Some(user) => match &user.name {
+                Some(name) => format!("{} ({})", user.username, name),
+                None => user.username.clone(),
+            },
+            None => "-".to_string(),
+        }),
+    );
+    field("Queued", Span::raw(secs(job.queued_duration)));
+    field("Started", Span::raw(time(&job.started_at)));
+    field("Finished", Span::raw(time(&job.finished_at)));
+    field("Duration", Span::raw(secs(job.duration)));
+    field(
+        "Coverage",
+        Span::raw(
+            job.coverage
+                .map(|c| format!("{:.1}%", c))
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+    );
+    let artifacts: Vec<String> = job
+        .artifacts
+        .iter()
+        .map(|a| match a.size {
+            Some(size) => format!("{} ({})", a.file_type, format_size(size)),
+            None => a.file_type.clone(),
+        })
+        .collect();
+    field(
+        "Artifacts",
+        Span::raw(if artifacts.is_empty() {
+            "-".to_string()
+        } else {
+            artifacts.join(", ")
+        }),
+    );
+    field(
+        "URL",
+        Span::raw(job.web_url.clone().unwrap_or_else(|| "-".to_string())),
+    );
+
+    let area = centered_rect(80, lines.len() as u16 + 2, target);
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
 fn draw_gitlab_config_dialog(
     f: &mut Frame,
     target: Rect,
@@ -0,0 +1,227 @@
+//! Parser for ANSI escape sequences as emitted by `git-graph` and CI job traces.
+//!
+//! SGR sequences (`ESC [ ... m`) are translated to styles: the 16 basic colors are mapped to
+//! the Nord palette, 256-color and truecolor codes are kept, and text attributes are applied
+//! cumulatively until reset. Other control sequences (cursor movement, line erase, OSC titles
+//! and hyperlinks) are removed. A carriage return or `ESC [2K` within the line discards the
+//! text before it, like a terminal overwriting a progress line.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use crate::theme;
+
+/// Splits text into segments of equal style. Never returns an empty list.
+pub fn parse(input: &str) -> Vec<(String, Style)> {
+    let mut segments: Vec<(String, Style)> = Vec::new();
+    let mut style = Style::default();
+    let mut text = String::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut command = None;
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            command = Some(c);
+                            break;
+                        }
+                        params.push(c);
+                    }
+                    match command {
+                        Some('m') => {
+                            flush(&mut segments, &mut text, style);
+                            style = apply_sgr(style, &params);
+                        }
+                        Some('K') if params == "2" => {
+                            segments.clear();
+                            text.clear();
+                        }
+                        _ => {}
+                    }
+                }
+                Some(']') => {
+                    // Operating system command, terminated by BEL or ST (`ESC \`)
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                Some('(') | Some(')') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\r' => {
+                if chars.peek().is_some_and(|c| *c != '\n') {
+                    segments.clear();
+                    text.clear();
+                }
+            }
+            '\t' => text.push(ch),
+            c if c.is_control() => {}
+            c => text.push(c),
+        }
+    }
+    flush(&mut segments, &mut text, style);
+
+    if segments.is_empty() {
+        segments.push((String::new(), Style::default()));
+    }
+    segments
+}
+
+/// The text without any escape sequences.
+pub fn strip(input: &str) -> String {
+    parse(input).into_iter().map(|(text, _)| text).collect()
+}
+
+/// Parses a single line of text into a styled line.
+pub fn to_line(input: &str) -> Line<'static> {
+    Line::from(
+        parse(input)
+            .into_iter()
+            .map(|(text, style)| Span::styled(text, style))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn flush(segments: &mut Vec<(String, Style)>, text: &mut String, style: Style) {
+    if text.is_empty() {
+        return;
+    }
+    match segments.last_mut() {
+        Some((last, last_style)) if *last_style == style => last.push_str(text),
+        _ => segments.push((text.clone(), style)),
+    }
+    text.clear();
+}
+
+/// Applies the parameters of an SGR sequence to the current style.
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let params: Vec<&str> = params.split(';').collect();
+    let mut i = 0;
+    while i < params.len() {
+        let param = params[i];
+        i += 1;
+
+        // Extended colors in the ITU form `38:5:n` or `38:2:[cs]:r:g:b`
+        if param.contains(':') {
+            let sub: Vec<&str> = param.split(':').collect();
+            let args = match sub.get(1) {
+                Some(&"2") if sub.len() > 5 => &sub[3..],
+                _ => &sub[2.min(sub.len())..],
+            };
+            let color = sub.get(1).and_then(|mode| extended_color(mode, args));
+            style = set_extended(style, sub[0], color);
+            continue;
+        }
+
+        let code: u16 = if param.is_empty() {
+            0
+        } else {
+            match param.parse() {
+                Ok(code) => code,
+                Err(_) => continue,
+            }
+        };
+        style = match code {
+            0 => Style::default(),
+            1 => add(style, Modifier::BOLD),
+            2 => add(style, Modifier::DIM),
+            3 => add(style, Modifier::ITALIC),
+            4 => add(style, Modifier::UNDERLINED),
+            5 => add(style, Modifier::SLOW_BLINK),
+            6 => add(style, Modifier::RAPID_BLINK),
+            7 => add(style, Modifier::REVERSED),
+            8 => add(style, Modifier::HIDDEN),
+            9 => add(style, Modifier::CROSSED_OUT),
+            21 | 22 => remove(style, Modifier::BOLD | Modifier::DIM),
+            23 => remove(style, Modifier::ITALIC),
+            24 => remove(style, Modifier::UNDERLINED),
+            25 => remove(style, Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+            27 => remove(style, Modifier::REVERSED),
+            28 => remove(style, Modifier::HIDDEN),
+            29 => remove(style, Modifier::CROSSED_OUT),
+            30..=37 => style.fg(palette(code as u8 - 30)),
+            39 => Style { fg: None, ..style },
+            40..=47 => style.bg(palette(code as u8 - 40)),
+            49 => Style { bg: None, ..style },
+            90..=97 => style.fg(palette(code as u8 - 90 + 8)),
+            100..=107 => style.bg(palette(code as u8 - 100 + 8)),
+            38 | 48 | 58 => {
+                let mode = params.get(i).copied().unwrap_or("");
+                let count = match mode {
+                    "5" => 1,
+                    "2" => 3,
+                    _ => 0,
+                };
+                let args = &params[(i + 1).min(params.len())..(i + 1 + count).min(params.len())];
+                let color = extended_color(mode, args);
+                i = (i + 1 + count).min(params.len());
+                set_extended(style, param, color)
+            }
+            _ => style,
+        };
+    }
+    style
+}
+
+fn add(mut style: Style, modifier: Modifier) -> Style {
+    style.add_modifier.insert(modifier);
+    style
+}
+
+fn remove(mut style: Style, modifier: Modifier) -> Style {
+    style.add_modifier.remove(modifier);
+    style
+}
+
+/// Color of a `5;n` (256-color) or `2;r;g;b` (truecolor) argument list.
+fn extended_color(mode: &str, args: &[&str]) -> Option<Color> {
+    let nums: Vec<u8> = args.iter().map(|a| a.parse().ok()).collect::<Option<_>>()?;
+    match (mode, nums.as_slice()) {
+        ("5", [n]) => Some(palette(*n)),
+        ("2", [r, g, b]) => Some(Color::Rgb(*r, *g, *b)),
+        _ => None,
+    }
+}
+
+fn set_extended(style: Style, target: &str, color: Option<Color>) -> Style {
+    match (target, color) {
+        ("38", Some(color)) => style.fg(color),
+        ("48", Some(color)) => style.bg(color),
+        _ => style,
+    }
+}
+
+/// Maps the 16 basic colors to the Nord palette; other 256-color indices are kept.
+fn palette(index: u8) -> Color {
+    match index {
+        // Standard colors (0-7)
+        0 => theme::polar_night::NORD0, // black → dark background
+        1 => theme::aurora::NORD11,     // red → Nord red
+        2 => theme::aurora::NORD14,     // green → Nord green
+        3 => theme::aurora::NORD13,     // yellow → Nord yellow
+        4 => theme::frost::NORD10,      // blue → Nord dark blue
+        5 => theme::aurora::NORD15,     // magenta → Nord purple
+        6 => theme::frost::NORD8,       // cyan → Nord cyan
+        7 => theme::snow_storm::NORD4,  // white → Nord light gray
+
+        // Bright colors (8-15)
+        8 => theme::polar_night::NORD3, // bright black → Nord comment gray
+        9 => theme::aurora::NORD11,     // bright red → Nord red
+        10 => theme::aurora::NORD14,    // bright green → Nord green
+        11 => theme::aurora::NORD13,    // bright yellow → Nord yellow
+        12 => theme::frost::NORD9,      // bright blue → Nord blue
+        13 => theme::aurora::NORD15,    // bright magenta → Nord purple
+        14 => theme::frost::NORD7,      // bright cyan → Nord teal
+        15 => theme::snow_storm::NORD6, // bright white → Nord white
+
+        n => Color::Indexed(n),
+    }
+}
@@ -0,0 +1,60 @@
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Opens a URL with `$BROWSER`, or else with the default handler of the platform
+/// (`open`, `xdg-open` or the Windows URL handler).
+pub fn open(url: &str) -> Result<(), String> {
+    let mut command = match std::env::var("BROWSER")
+        .ok()
+        .filter(|b| !b.trim().is_empty())
+    {
+        Some(browser) => browser_command(&browser, url)?,
+        None if cfg!(target_os = "macos") => {
+            let mut command = Command::new("open");
+            command.arg(url);
+            command
+        }
+        None if cfg!(windows) => {
+            let mut command = Command::new("rundll32");
+            command.args(["url.dll,FileProtocolHandler", url]);
+            command
+        }
+        None => {
+            let mut command = Command::new("xdg-open");
+            command.arg(url);
+            command
+        }
+    };
+
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("Failed to start {}: {}", program, err))?;
+    // Reap the process without blocking the UI
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// `$BROWSER` may list several `:`-separated commands, of which the first is used,
+/// and mark the position of the URL with `%s`.
+fn browser_command(browser: &str, url: &str) -> Result<Command, String> {
+    let first = browser.split(':').next().unwrap_or_default();
+    let mut parts = first.split_whitespace();
+    let mut command = Command::new(
+        parts
+            .next()
+            .ok_or_else(|| format!("Invalid $BROWSER '{}'", browser))?,
+    );
+    let mut has_placeholder = false;
+    for arg in parts {
+        has_placeholder |= arg.contains("%s");
+        command.arg(arg.replace("%s", url));
+    }
+    if !has_placeholder {
+        command.arg(url);
+    }
+    Ok(command)
+}
@@ -0,0 +1,83 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Larger payloads are often dropped by terminals, so OSC 52 is skipped for them.
+const OSC52_MAX_LEN: usize = 100_000;
+
+/// Copies text to the clipboard.
+///
+/// With a graphical session (Wayland, X11, macOS or Windows), the native clipboard
+/// tools of the platform are used, as some terminals ignore OSC 52. Without one, e.g.
+/// over SSH, and if no native tool works, the text is sent as an OSC 52 escape
+/// sequence, which the terminal may or may not apply.
+pub fn copy(text: &str) -> Result<(), String> {
+    if (has_display() && copy_native(text)) || copy_osc52(text) {
+        Ok(())
+    } else if STANDARD.encode(text).len() > OSC52_MAX_LEN {
+        Err("Too large to copy through the terminal. Install wl-copy, xclip or xsel.".to_string())
+    } else {
+        Err("No clipboard available. Install wl-copy, xclip or xsel, or use a terminal supporting OSC 52.".to_string())
+    }
+}
+
+fn copy_osc52(text: &str) -> bool {
+    if !std::io::stdout().is_terminal() {
+        return false;
+    }
+    let encoded = STANDARD.encode(text);
+    if encoded.len() > OSC52_MAX_LEN {
+        return false;
+    }
+    let sequence = if std::env::var_os("TMUX").is_some() {
+        // Pass through to the outer terminal
+        format!("\x1bPtmux;\x1b\x1b]52;c;{}\x07\x1b\\", encoded)
+    } else {
+        format!("\x1b]52;c;{}\x07", encoded)
+    };
+    let mut out = std::io::stdout();
+    out.write_all(sequence.as_bytes()).is_ok() && out.flush().is_ok()
+}
+
+fn has_display() -> bool {
+    cfg!(any(target_os = "macos", windows))
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+        || std::env::var_os("DISPLAY").is_some()
+}
+
+fn copy_native(text: &str) -> bool {
+    let candidates: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else if cfg!(windows) {
+        &[("clip", &[])]
+    } else {
+        &[
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    };
+
+    candidates
+        .iter()
+        .any(|(program, args)| pipe_to(program, args, text))
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> bool {
+    let child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(text.as_bytes()).is_ok(),
+        None => false,
+    };
+    matches!(child.wait(), Ok(status) if status.success()) && written
+}
@@ -1,3 +1,7 @@
-pub mod ctrl_chars;
+pub mod ansi;
+pub mod browser;
+pub mod clipboard;
 pub mod format;
+pub mod notify;
 pub mod syntax_highlight;
+pub mod time;
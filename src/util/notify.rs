@@ -0,0 +1,78 @@
+use crate::gitlab_config::{NotificationConfig, NotificationMethod};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Sends a notification that the pipeline of `branch` finished with `status`.
+pub fn pipeline_finished(config: &NotificationConfig, branch: &str, status: &str, failed: bool) {
+    let message = if failed {
+        format!("Pipeline on {} failed ({})", branch, status)
+    } else if status == "manual" {
+        format!("Pipeline on {} is waiting for a manual job", branch)
+    } else {
+        format!("Pipeline on {} finished ({})", branch, status)
+    };
+
+    match config.method {
+        NotificationMethod::Osc9 => {
+            write_terminal(&passthrough(&format!("\x1b]9;{}\x07", sanitize(&message))))
+        }
+        NotificationMethod::Osc777 => write_terminal(&passthrough(&format!(
+            "\x1b]777;notify;git-igitt;{}\x07",
+            sanitize(&message)
+        ))),
+        NotificationMethod::Bell => write_terminal("\x07"),
+        NotificationMethod::Command => {
+            if let Some(command) = &config.command {
+                run_command(command, branch, status, &message);
+            }
+        }
+        NotificationMethod::None => {}
+    }
+}
+
+fn write_terminal(sequence: &str) {
+    let mut out = std::io::stdout();
+    let _ = out.write_all(sequence.as_bytes());
+    let _ = out.flush();
+}
+
+/// Wraps an OSC sequence for tmux, which doesn't forward unknown OSC sequences to
+/// the outer terminal by itself.
+fn passthrough(sequence: &str) -> String {
+    if std::env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence.to_string()
+    }
+}
+
+/// Strips characters that would terminate or break the escape sequence.
+fn sanitize(message: &str) -> String {
+    message
+        .chars()
+        .filter(|c| !c.is_control() && *c != ';')
+        .collect()
+}
+
+fn run_command(command: &str, branch: &str, status: &str, message: &str) {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    let child = cmd
+        .env("IGITT_BRANCH", branch)
+        .env("IGITT_STATUS", status)
+        .env("IGITT_MESSAGE", message)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Ok(mut child) = child {
+        std::thread::spawn(move || child.wait());
+    }
+}
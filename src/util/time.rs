@@ -0,0 +1,107 @@
+//! Timestamps as returned by the GitLab API, e.g. `2024-05-01T12:34:56.789Z`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Parses an ISO 8601 timestamp with a `Z` or `±HH:MM` offset into seconds since the Unix epoch.
+pub fn parse_timestamp(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let num = |range: std::ops::Range<usize>| -> Option<i64> { s.get(range)?.parse().ok() };
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+
+    let rest = s.get(19..)?;
+    let frac_len = rest
+        .strip_prefix('.')
+        .map(|r| r.bytes().take_while(u8::is_ascii_digit).count() + 1)
+        .unwrap_or(0);
+    let fraction: f64 = if frac_len > 1 {
+        format!("0{}", &rest[..frac_len]).parse().ok()?
+    } else {
+        0.0
+    };
+    let offset = match &rest[frac_len..] {
+        "" | "Z" | "z" => 0,
+        tz => {
+            let sign = match tz.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let tz = tz[1..].replace(':', "");
+            let hours: i64 = tz.get(0..2)?.parse().ok()?;
+            let minutes: i64 = tz.get(2..4).map_or(Some(0), |m| m.parse().ok())?;
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+
+    let days = days_from_civil(year, month, day);
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second - offset;
+    Some(seconds as f64 + fraction)
+}
+
+/// Current time in seconds since the Unix epoch.
+pub fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_days_since_the_epoch() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(days_from_civil(2000, 2, 29), 11016);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(2024, 2, 29), 19782);
+        assert_eq!(days_from_civil(1900, 3, 1), -25508);
+        assert_eq!(days_from_civil(1600, 1, 1), -135140);
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0.0));
+        assert_eq!(parse_timestamp("2038-01-19T03:14:07Z"), Some(2147483647.0));
+        assert_eq!(
+            parse_timestamp("2024-05-01T12:34:56.789Z"),
+            Some(1714566896.789)
+        );
+        assert_eq!(parse_timestamp(" 2024-05-01T12:34:56 "), Some(1714566896.0));
+        assert_eq!(
+            parse_timestamp("2024-05-01T14:04:56.000+01:30"),
+            Some(1714566896.0)
+        );
+        assert_eq!(
+            parse_timestamp("2024-05-01T08:34:56-0400"),
+            Some(1714566896.0)
+        );
+        assert_eq!(
+            parse_timestamp("2024-05-01T14:34:56+02"),
+            Some(1714566896.0)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_timestamps() {
+        assert_eq!(parse_timestamp(""), None);
+        assert_eq!(parse_timestamp("2024-05-01"), None);
+        assert_eq!(parse_timestamp("2024-05-01T12:34"), None);
+        assert_eq!(parse_timestamp("2024-05-01T12:34:56 UTC"), None);
+        assert_eq!(parse_timestamp("yesterday at noon!!"), None);
+    }
+}
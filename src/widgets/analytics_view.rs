@@ -0,0 +1,63 @@
+use crate::gitlab::analytics::JobAnalytics;
+use ratatui::widgets::TableState;
+
+pub struct AnalyticsViewState {
+    pub ref_name: String,
+    pub analytics: JobAnalytics,
+    pub state: TableState,
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+impl AnalyticsViewState {
+    pub fn new(ref_name: String) -> AnalyticsViewState {
+        AnalyticsViewState {
+            ref_name,
+            analytics: JobAnalytics::default(),
+            state: TableState::default(),
+            loading: true,
+            error: None,
+        }
+    }
+
+    pub fn set_analytics(&mut self, analytics: JobAnalytics) {
+        self.state.select(if analytics.jobs.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        self.analytics = analytics;
+        self.loading = false;
+        self.error = None;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.loading = false;
+        self.error = Some(error);
+    }
+
+    pub fn fwd(&mut self, steps: usize) {
+        if self.analytics.jobs.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => std::cmp::min(
+                i.saturating_add(steps),
+                self.analytics.jobs.len().saturating_sub(1),
+            ),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn bwd(&mut self, steps: usize) {
+        if self.analytics.jobs.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => i.saturating_sub(steps),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+}
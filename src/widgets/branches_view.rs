@@ -1,3 +1,4 @@
+use crate::gitlab::models::PipelineStatus;
 use crate::widgets::list::{ListItem, ListState};
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -23,9 +24,71 @@ impl BranchItemType {
     }
 }
 
+/// Order of the entries within each section of the branch list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BranchSort {
+    #[default]
+    Name,
+    /// Failed tip pipelines first, branches without pipeline last.
+    Status,
+}
+
+impl BranchSort {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Name => Self::Status,
+            Self::Status => Self::Name,
+        }
+    }
+}
+
+/// Shows only branches whose tip pipeline is in a group of states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchFilter {
+    Failed,
+    Running,
+    Success,
+}
+
+impl BranchFilter {
+    /// Cycles through no filter and the filters.
+    pub fn next(filter: Option<Self>) -> Option<Self> {
+        match filter {
+            None => Some(Self::Failed),
+            Some(Self::Failed) => Some(Self::Running),
+            Some(Self::Running) => Some(Self::Success),
+            Some(Self::Success) => None,
+        }
+    }
+
+    pub fn matches(&self, status: Option<PipelineStatus>) -> bool {
+        match (self, status) {
+            (Self::Failed, Some(PipelineStatus::Failed)) => true,
+            (Self::Running, Some(status)) => status.is_active(),
+            (Self::Success, Some(PipelineStatus::Success)) => true,
+            _ => false,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Failed => "failed",
+            Self::Running => "running",
+            Self::Success => "passed",
+        }
+    }
+}
+
+/// Sort rank of a tip pipeline status: the ones needing attention first.
+pub fn status_rank(status: Option<PipelineStatus>) -> u8 {
+    status.map_or(6, |s| s.severity())
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BranchListItem<'a> {
     pub content: Span<'a>,
+    /// Pipeline status glyph shown in front of the name.
+    pub badge: Option<Span<'a>>,
     pub style: Style,
     pub item_type: &'a BranchItemType,
 }
@@ -37,11 +100,17 @@ impl<'a> BranchListItem<'a> {
     {
         BranchListItem {
             content: content.into(),
+            badge: None,
             style: Style::default(),
             item_type,
         }
     }
 
+    pub fn badge(mut self, badge: Span<'a>) -> BranchListItem<'a> {
+        self.badge = Some(badge);
+        self
+    }
+
     pub fn style(mut self, style: Style) -> BranchListItem<'a> {
         self.style = style;
         self
@@ -203,6 +272,15 @@ impl StatefulWidget for BranchList<'_> {
                 x
             };
 
+            let elem_x = match &item.badge {
+                Some(badge) => {
+                    let width = list_area.width.saturating_sub(elem_x - x);
+                    let (badge_x, _) = buf.set_span(elem_x, y, badge, width);
+                    (badge_x + 1).min(x + list_area.width)
+                }
+                None => elem_x,
+            };
+
             let max_element_width = (list_area.width - (elem_x - x)) as usize;
 
             if state.scroll_x > 0 && item.content.content.width() > max_element_width {
@@ -1,5 +1,5 @@
 use crate::app::DiffType;
-use crate::util::ctrl_chars::CtrlChars;
+use crate::util::ansi;
 use crate::widgets::list::{ListItem, StatefulList};
 use git2::Oid;
 use ratatui::buffer::Buffer;
@@ -121,23 +121,9 @@ impl StatefulWidget for CommitView<'_> {
                     };
 
                     for line in wrapped.lines() {
-                        let mut x = x_start;
-                        let mut remaining_width = max_element_width;
-
-                        let line_span = CtrlChars::parse(line).into_text();
                         if y >= y0 as i32 {
-                            for txt in line_span {
-                                for line in txt.lines {
-                                    if remaining_width == 0 {
-                                        break;
-                                    }
-                                    let pos = buf.set_line(x, y as u16, &line, remaining_width);
-                                    let w = pos.0.saturating_sub(x);
-                                    x = pos.0;
-                                    y = pos.1 as i32;
-                                    remaining_width = remaining_width.saturating_sub(w);
-                                }
-                            }
+                            let line = ansi::to_line(line);
+                            buf.set_line(x_start, y as u16, &line, max_element_width);
                         }
                         y += 1;
                         if y >= list_bottom as i32 {
@@ -0,0 +1,67 @@
+use crate::gitlab::compare::{compare_pipelines, JobComparison};
+use crate::gitlab::models::{Pipeline, PipelineDetails};
+use ratatui::widgets::TableState;
+
+pub struct CompareViewState {
+    /// The older of the two commits.
+    pub base_sha: String,
+    pub head_sha: String,
+    pub base: Option<Pipeline>,
+    pub head: Option<Pipeline>,
+    pub jobs: Vec<JobComparison>,
+    pub state: TableState,
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+impl CompareViewState {
+    pub fn new(base_sha: String, head_sha: String) -> CompareViewState {
+        CompareViewState {
+            base_sha,
+            head_sha,
+            base: None,
+            head: None,
+            jobs: vec![],
+            state: TableState::default(),
+            loading: true,
+            error: None,
+        }
+    }
+
+    pub fn set_pipelines(&mut self, base: PipelineDetails, head: PipelineDetails) {
+        self.jobs = compare_pipelines(&base, &head);
+        self.state
+            .select(if self.jobs.is_empty() { None } else { Some(0) });
+        self.base = base.pipeline;
+        self.head = head.pipeline;
+        self.loading = false;
+        self.error = None;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.loading = false;
+        self.error = Some(error);
+    }
+
+    pub fn fwd(&mut self, steps: usize) {
+        if self.jobs.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => std::cmp::min(i.saturating_add(steps), self.jobs.len().saturating_sub(1)),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn bwd(&mut self, steps: usize) {
+        if self.jobs.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => i.saturating_sub(steps),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+}
@@ -12,6 +12,8 @@ pub struct DiffViewInfo {
     pub oid: Oid,
     pub compare_oid: Oid,
     pub scroll: (u16, u16),
+    /// Line of the file to highlight (in new/old file mode).
+    pub marked_line: Option<u32>,
 }
 impl DiffViewInfo {
     pub fn new(
@@ -26,6 +28,7 @@ impl DiffViewInfo {
             oid,
             compare_oid,
             scroll: (0, 0),
+            marked_line: None,
         }
     }
 }
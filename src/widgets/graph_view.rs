@@ -1,5 +1,7 @@
+use crate::bisect::Bisect;
 use crate::gitlab::models::PipelineStatus;
-use crate::util::ctrl_chars::CtrlChars;
+use crate::theme;
+use crate::util::ansi;
 use crate::widgets::branches_view::BranchItem;
 use crate::widgets::list::StatefulList;
 use git_graph::graph::GitGraph;
@@ -15,6 +17,71 @@ const SCROLL_MARGIN: usize = 3;
 const SCROLLBAR_STR: &str = "\u{2588}";
 const SHA_LENGTH: u16 = 7;
 
+/// A group of pipeline states to jump to or filter the graph by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusGroup {
+    Failed,
+    Running,
+    Passed,
+}
+
+impl StatusGroup {
+    pub fn matches(&self, status: Option<PipelineStatus>) -> bool {
+        match (self, status) {
+            (Self::Failed, Some(PipelineStatus::Failed)) => true,
+            (Self::Running, Some(status)) => status.is_active(),
+            (Self::Passed, Some(PipelineStatus::Success)) => true,
+            _ => false,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Failed => "failed",
+            Self::Running => "running",
+            Self::Passed => "passed",
+        }
+    }
+}
+
+/// Shows the commits whose pipeline is in one of a set of groups.
+///
+/// Other commits are dimmed rather than hidden, so the graph's lines stay connected.
+/// Searches and status jumps skip them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphFilter(&'static [StatusGroup]);
+
+const GRAPH_FILTERS: &[GraphFilter] = &[
+    GraphFilter(&[StatusGroup::Failed]),
+    GraphFilter(&[StatusGroup::Failed, StatusGroup::Running]),
+    GraphFilter(&[StatusGroup::Running]),
+    GraphFilter(&[StatusGroup::Passed]),
+];
+
+impl GraphFilter {
+    /// Cycles through no filter and the filters: failed, failed or running, running,
+    /// passed.
+    pub fn next(filter: Option<Self>) -> Option<Self> {
+        let next = match filter {
+            Some(filter) => GRAPH_FILTERS
+                .iter()
+                .position(|f| *f == filter)
+                .map(|i| i + 1),
+            None => Some(0),
+        };
+        next.and_then(|i| GRAPH_FILTERS.get(i)).copied()
+    }
+
+    pub fn matches(&self, status: Option<PipelineStatus>) -> bool {
+        self.0.iter().any(|group| group.matches(status))
+    }
+
+    pub fn label(&self) -> String {
+        let labels: Vec<&str> = self.0.iter().map(|group| group.label()).collect();
+        labels.join("/")
+    }
+}
+
 #[derive(Default)]
 pub struct GraphViewState {
     pub graph: Option<GitGraph>,
@@ -28,6 +95,10 @@ pub struct GraphViewState {
     pub secondary_changed: bool,
     pub pipeline_statuses: HashMap<String, PipelineStatus>,
     pub animation_tick: u8,
+    pub status_filter: Option<GraphFilter>,
+    pub bisect: Option<Bisect>,
+    /// IIDs of open merge requests by their source branch tip.
+    pub merge_requests: HashMap<String, u64>,
 }
 
 impl GraphViewState {
@@ -38,6 +109,30 @@ impl GraphViewState {
         let status = self.pipeline_statuses.get(&sha)?;
         Some((*status, self.animation_tick))
     }
+
+    pub fn pipeline_status(&self, commit_idx: usize) -> Option<PipelineStatus> {
+        self.pipeline_info(commit_idx).map(|(status, _)| status)
+    }
+
+    /// Whether the commit is dimmed by the status filter.
+    pub fn is_dimmed(&self, commit_idx: usize) -> bool {
+        self.status_filter
+            .is_some_and(|filter| !filter.matches(self.pipeline_status(commit_idx)))
+    }
+
+    /// Index of the next older (or newer) commit than the selected one whose pipeline
+    /// is in the group and that isn't dimmed.
+    pub fn find_by_status(&self, group: StatusGroup, older: bool) -> Option<usize> {
+        let count = self.graph.as_ref()?.commits.len();
+        let selected = self.selected?;
+        let matches =
+            |idx: &usize| group.matches(self.pipeline_status(*idx)) && !self.is_dimmed(*idx);
+        if older {
+            (selected + 1..count).find(matches)
+        } else {
+            (0..selected).rev().find(matches)
+        }
+    }
 }
 
 fn pipeline_base_color(status: PipelineStatus) -> (u8, u8, u8) {
@@ -55,7 +150,7 @@ fn pipeline_base_color(status: PipelineStatus) -> (u8, u8, u8) {
     }
 }
 
-fn pipeline_status_to_color(status: PipelineStatus) -> Color {
+pub(crate) fn pipeline_status_to_color(status: PipelineStatus) -> Color {
     let (r, g, b) = pipeline_base_color(status);
     Color::Rgb(r, g, b)
 }
@@ -316,24 +411,56 @@ impl StatefulWidget for GraphView<'_> {
                 Some((sha.to_string(), status, tick))
             });
 
-            let mut body = CtrlChars::parse(graph_item).into_text();
-            body.extend(CtrlChars::parse(&format!("  {}", text_item)).into_text());
+            let mut line = ansi::to_line(graph_item);
+            let graph_width = line.width() as u16;
+            line.spans
+                .extend(ansi::to_line(&format!("  {}", text_item)).spans);
+            buf.set_line(elem_x, y, &line, max_element_width as u16);
 
-            let mut x = elem_x;
-            let mut remaining_width = max_element_width as u16;
-            for txt in body {
-                for line in txt.lines {
-                    if remaining_width == 0 {
-                        break;
-                    }
-                    let pos = buf.set_line(x, y, &line, remaining_width);
-                    let w = pos.0.saturating_sub(x);
-                    x = pos.0;
-                    remaining_width = remaining_width.saturating_sub(w);
+            let commit_idx = state.commit_index_for_line(i);
+            let text_x = (elem_x + graph_width).min(list_area.right());
+            let text_area = Rect {
+                x: text_x,
+                y,
+                width: list_area.right() - text_x,
+                height: 1,
+            };
+            let dimmed = commit_idx.is_some_and(|idx| state.is_dimmed(idx));
+            if dimmed {
+                buf.set_style(text_area, Style::default().fg(theme::TEXT_DIM));
+            }
+
+            let commit_oid =
+                commit_idx.and_then(|idx| Some(state.graph.as_ref()?.commits.get(idx)?.oid));
+            let has_merge_request =
+                commit_oid.is_some_and(|oid| state.merge_requests.contains_key(&oid.to_string()));
+            if has_merge_request && text_area.width > 1 {
+                buf.set_string(text_x + 1, y, "!", Style::default().fg(theme::SPECIAL));
+            }
+
+            let bisect_oid = commit_idx.and_then(|idx| {
+                let commit = state.graph.as_ref()?.commits.get(idx)?;
+                Some((state.bisect.as_ref()?, commit.oid))
+            });
+            if let Some((bisect, oid)) = bisect_oid {
+                if bisect.in_range(&oid) {
+                    buf.set_style(text_area, Style::default().bg(theme::BG_ELEVATED));
+                }
+                let marker = if bisect.next == Some(oid) {
+                    Some(("▸", theme::WARNING))
+                } else if bisect.bad == Some(oid) {
+                    Some(("✗", theme::ERROR))
+                } else if bisect.is_good(&oid) {
+                    Some(("✓", theme::SUCCESS))
+                } else {
+                    None
+                };
+                if let (Some((symbol, color)), true) = (marker, text_area.width > 0) {
+                    buf.set_string(text_x, y, symbol, Style::default().fg(color));
                 }
             }
 
-            if let Some((sha, status, tick)) = commit_info {
+            if let Some((sha, status, tick)) = commit_info.filter(|_| !dimmed) {
                 for search_x in elem_x..list_area.right() {
                     let mut found = true;
                     for (offset, sha_char) in sha.chars().enumerate() {
@@ -415,3 +542,32 @@ impl Widget for GraphView<'_> {
         StatefulWidget::render(self, area, buf, &mut state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_graph_filters() {
+        let mut labels = vec![];
+        let mut filter = GraphFilter::next(None);
+        while let Some(f) = filter {
+            labels.push(f.label());
+            filter = GraphFilter::next(filter);
+        }
+        assert_eq!(
+            labels,
+            vec!["failed", "failed/running", "running", "passed"]
+        );
+    }
+
+    #[test]
+    fn matches_statuses_in_any_group() {
+        let filter = GraphFilter(&[StatusGroup::Failed, StatusGroup::Running]);
+        assert!(filter.matches(Some(PipelineStatus::Failed)));
+        assert!(filter.matches(Some(PipelineStatus::Pending)));
+        assert!(!filter.matches(Some(PipelineStatus::Success)));
+        assert!(!filter.matches(Some(PipelineStatus::Canceled)));
+        assert!(!filter.matches(None));
+    }
+}
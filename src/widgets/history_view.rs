@@ -0,0 +1,64 @@
+use crate::gitlab::models::Pipeline;
+use ratatui::widgets::ListState;
+
+pub struct HistoryViewState {
+    pub ref_name: String,
+    pub pipelines: Vec<Pipeline>,
+    pub state: ListState,
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+impl HistoryViewState {
+    pub fn new(ref_name: String) -> HistoryViewState {
+        HistoryViewState {
+            ref_name,
+            pipelines: vec![],
+            state: ListState::default(),
+            loading: true,
+            error: None,
+        }
+    }
+
+    pub fn set_pipelines(&mut self, pipelines: Vec<Pipeline>) {
+        self.state
+            .select(if pipelines.is_empty() { None } else { Some(0) });
+        self.pipelines = pipelines;
+        self.loading = false;
+        self.error = None;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.loading = false;
+        self.error = Some(error);
+    }
+
+    pub fn fwd(&mut self, steps: usize) {
+        if self.pipelines.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => std::cmp::min(
+                i.saturating_add(steps),
+                self.pipelines.len().saturating_sub(1),
+            ),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn bwd(&mut self, steps: usize) {
+        if self.pipelines.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => i.saturating_sub(steps),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn selected_pipeline(&self) -> Option<&Pipeline> {
+        self.state.selected().and_then(|i| self.pipelines.get(i))
+    }
+}
@@ -0,0 +1,54 @@
+use crate::gitlab::models::LintResult;
+use syntect::highlighting::Style;
+
+pub struct LintViewState {
+    /// Commit the configuration was read from.
+    pub sha: String,
+    /// Ref the pipeline creation is simulated for.
+    pub ref_name: Option<String>,
+    pub result: Option<LintResult>,
+    /// Syntax highlighted merged YAML, if colors are enabled.
+    pub highlighted: Option<Vec<Vec<(Style, String)>>>,
+    pub scroll: u16,
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+impl LintViewState {
+    pub fn new(sha: String, ref_name: Option<String>) -> LintViewState {
+        LintViewState {
+            sha,
+            ref_name,
+            result: None,
+            highlighted: None,
+            scroll: 0,
+            loading: true,
+            error: None,
+        }
+    }
+
+    pub fn set_result(
+        &mut self,
+        result: LintResult,
+        highlighted: Option<Vec<Vec<(Style, String)>>>,
+    ) {
+        self.result = Some(result);
+        self.highlighted = highlighted;
+        self.scroll = 0;
+        self.loading = false;
+        self.error = None;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.loading = false;
+        self.error = Some(error);
+    }
+
+    pub fn fwd(&mut self, steps: usize) {
+        self.scroll = self.scroll.saturating_add(steps as u16);
+    }
+
+    pub fn bwd(&mut self, steps: usize) {
+        self.scroll = self.scroll.saturating_sub(steps as u16);
+    }
+}
@@ -1,8 +1,14 @@
+pub mod analytics_view;
 pub mod branches_view;
 pub mod commit_view;
+pub mod compare_view;
 pub mod diff_view;
 pub mod files_view;
 pub mod graph_view;
+pub mod history_view;
+pub mod lint_view;
 pub mod list;
 pub mod models_view;
 pub mod pipeline_view;
+pub mod problems_view;
+pub mod remotes_view;
@@ -1,10 +1,16 @@
-use crate::gitlab::models::{PipelineDetails, PipelineStatus, Stage};
+use crate::gitlab::models::{
+    Job, Pipeline, PipelineDetails, PipelineStatus, PipelineVariable, Stage,
+};
 use crate::theme;
+use crate::util::ansi;
+use crate::util::time;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::widgets::{Block, StatefulWidget, Widget};
+use regex::RegexBuilder;
 use std::collections::HashMap;
+use std::ops::Range;
 
 #[derive(Debug, Clone)]
 pub struct LogLine {
@@ -12,6 +18,73 @@ pub struct LogLine {
     pub content: String,
     pub styled: Vec<(String, Style)>,
     pub duration: Option<String>,
+    /// Index into the log's sections if this line is a section header.
+    pub section: Option<usize>,
+    /// Number of enclosing sections.
+    pub depth: usize,
+}
+
+/// A collapsible `section_start`/`section_end` block of a job log.
+#[derive(Debug, Clone)]
+pub struct LogSection {
+    pub name: String,
+    /// Index of the header line.
+    pub header: usize,
+    /// Index after the last line of the section.
+    pub end: usize,
+    pub collapsed: bool,
+}
+
+impl LogLine {
+    /// The line's text without escape sequences.
+    pub fn plain_text(&self) -> String {
+        self.styled.iter().map(|(text, _)| text.as_str()).collect()
+    }
+}
+
+/// Patterns that mark a failure in a job log, for "jump to first error".
+const ERROR_PATTERNS: &[&str] = &[
+    "error:",
+    "error[",
+    "ERROR:",
+    "Error:",
+    "FAILED",
+    "panicked at",
+    "fatal:",
+];
+
+/// Incremental search in the job log.
+#[derive(Debug, Clone, Default)]
+pub struct LogSearch {
+    pub query: String,
+    pub regex: bool,
+    /// Whether the query is being typed.
+    pub editing: bool,
+    pub error: Option<String>,
+    /// Line index and byte range in the line's plain text of each match.
+    pub matches: Vec<(usize, Range<usize>)>,
+    pub current: Option<usize>,
+}
+
+impl LogSearch {
+    pub fn is_active(&self) -> bool {
+        self.editing || !self.query.is_empty()
+    }
+
+    /// Match ranges in the given line, and the index of the current match among them.
+    pub fn line_matches(&self, line: usize) -> (Vec<Range<usize>>, Option<usize>) {
+        let start = self.matches.partition_point(|(l, _)| *l < line);
+        let ranges: Vec<_> = self.matches[start..]
+            .iter()
+            .take_while(|(l, _)| *l == line)
+            .map(|(_, r)| r.clone())
+            .collect();
+        let current = self
+            .current
+            .filter(|c| *c >= start && *c < start + ranges.len())
+            .map(|c| c - start);
+        (ranges, current)
+    }
 }
 
 const MIN_STAGE_WIDTH: u16 = 16;
@@ -21,7 +94,7 @@ const SCROLLBAR_STR: &str = "\u{2588}";
 
 #[derive(Debug, Clone)]
 pub enum CachedPipeline {
-    Found(PipelineDetails),
+    Found(Box<PipelineDetails>),
     NotFound,
     Error(String),
 }
@@ -38,8 +111,18 @@ pub struct PipelineViewState {
     pub loading: bool,
     cache: HashMap<String, CachedPipeline>,
     cache_order: Vec<String>,
+    /// Pipelines previewed from `.gitlab-ci.yml` for commits without one, by commit.
+    preview_cache: HashMap<String, CachedPipeline>,
+    preview_cache_order: Vec<String>,
     pub animation_tick: u8,
     pub job_log: Vec<LogLine>,
+    /// The unprocessed trace the job log was parsed from.
+    pub job_log_raw: String,
+    pub job_log_sections: Vec<LogSection>,
+    /// Indices of the job log lines not hidden in collapsed sections.
+    pub job_log_visible: Vec<usize>,
+    /// Sections collapsed or expanded by the user, kept across log refreshes.
+    section_overrides: HashMap<String, bool>,
     pub job_log_job_id: Option<u64>,
     pub job_log_scroll: u16,
     pub job_log_loading: bool,
@@ -47,6 +130,25 @@ pub struct PipelineViewState {
     job_log_cache: HashMap<u64, String>,
     pub job_log_focused: bool,
     pub job_log_visible_height: u16,
+    /// Short message shown in the job log title, e.g. where the log was exported to.
+    pub job_log_notice: Option<String>,
+    pub log_search: LogSearch,
+    /// Whether the details popup of the selected job is shown.
+    pub job_details_open: bool,
+    /// Whether the pipeline header lists the pipeline's variables.
+    pub show_variables: bool,
+    /// Whether jobs are shown as bars on a time axis instead of stage boxes.
+    pub timeline: bool,
+    /// Whether earlier attempts of retried jobs are loaded and shown.
+    pub include_retried: bool,
+    /// Id of the pipeline `info` and `variables` belong to.
+    info_id: Option<u64>,
+    info: Option<Pipeline>,
+    variables: Option<Result<Vec<PipelineVariable>, String>>,
+    /// Id of the pipeline whose info is being fetched.
+    info_pending: Option<u64>,
+    /// Pipelines chosen with the switcher for commits with several pipelines, by commit.
+    pinned: HashMap<String, u64>,
 }
 
 impl PipelineViewState {
@@ -67,32 +169,97 @@ impl PipelineViewState {
         self.cache.insert(sha, result);
     }
 
+    pub fn get_cached_preview(&self, sha: &str) -> Option<&CachedPipeline> {
+        self.preview_cache.get(sha)
+    }
+
+    pub fn cache_preview(&mut self, sha: String, result: CachedPipeline) {
+        if self.preview_cache.len() >= MAX_CACHE_SIZE {
+            if let Some(old_sha) = self.preview_cache_order.first().cloned() {
+                self.preview_cache.remove(&old_sha);
+                self.preview_cache_order.remove(0);
+            }
+        }
+        if !self.preview_cache.contains_key(&sha) {
+            self.preview_cache_order.push(sha.clone());
+        }
+        self.preview_cache.insert(sha, result);
+    }
+
+    pub fn pinned_pipeline(&self, sha: &str) -> Option<u64> {
+        self.pinned.get(sha).copied()
+    }
+
+    pub fn pin_pipeline(&mut self, sha: String, pipeline_id: u64) {
+        self.pinned.insert(sha, pipeline_id);
+    }
+
+    pub fn clear_previews(&mut self) {
+        self.preview_cache.clear();
+        self.preview_cache_order.clear();
+    }
+
     pub fn invalidate_cache(&mut self, sha: &str) {
         self.cache.remove(sha);
         self.cache_order.retain(|s| s != sha);
     }
 
+    /// Shows or hides earlier attempts of retried jobs. Drops cached pipelines, which
+    /// were loaded with the previous setting.
+    pub fn toggle_include_retried(&mut self) {
+        self.include_retried = !self.include_retried;
+        self.cache.clear();
+        self.cache_order.clear();
+    }
+
     pub fn set_pipeline(&mut self, sha: Option<String>, details: Option<PipelineDetails>) {
         if let Some(sha) = &sha {
             let cached = match &details {
-                Some(d) => CachedPipeline::Found(d.clone()),
+                Some(d) => CachedPipeline::Found(Box::new(d.clone())),
                 None => CachedPipeline::NotFound,
             };
             self.cache_result(sha.clone(), cached);
         }
+        let pipeline_id = |d: &Option<PipelineDetails>| {
+            d.as_ref().and_then(|d| d.pipeline.as_ref()).map(|p| p.id)
+        };
+        // Reruns and pipelines chosen with the switcher replace the shown one for the same commit
         let same_sha = self.current_sha == sha;
+        let same_pipeline = same_sha
+            && (self.details.is_none() || pipeline_id(&self.details) == pipeline_id(&details));
         self.current_sha = sha;
         self.details = details;
-        if !same_sha {
+        if !same_pipeline {
             self.selected_stage = 0;
             self.selected_job = 0;
             self.scroll_x = 0;
             self.scroll_y = 0;
         }
+        if same_sha && !same_pipeline {
+            self.job_details_open = false;
+            self.clear_job_log();
+        }
         self.error = None;
         self.loading = false;
     }
 
+    /// Shows a pipeline preview built from the CI configuration. Previews are cached by
+    /// sha until [`Self::clear_previews`] on reload, as `rules:` depend on the branch at
+    /// the commit, or [`Self::clear_cache`].
+    pub fn set_preview(&mut self, sha: String, details: PipelineDetails) {
+        if self.current_sha.as_ref() != Some(&sha) {
+            self.selected_stage = 0;
+            self.selected_job = 0;
+            self.scroll_x = 0;
+            self.scroll_y = 0;
+        }
+        self.current_sha = Some(sha);
+        self.details = Some(details);
+        self.error = None;
+        self.loading = false;
+        self.clear_job_log();
+    }
+
     pub fn set_error(&mut self, sha: Option<String>, error: String) {
         if let Some(sha) = &sha {
             self.cache_result(sha.clone(), CachedPipeline::Error(error.clone()));
@@ -119,7 +286,7 @@ impl PipelineViewState {
         self.loading = false;
         match cached {
             CachedPipeline::Found(details) => {
-                self.details = Some(details.clone());
+                self.details = Some(details.as_ref().clone());
                 self.error = None;
             }
             CachedPipeline::NotFound => {
@@ -163,11 +330,19 @@ impl PipelineViewState {
         }
     }
 
+    /// Selects the next job of the stage, or in the timeline the next job of the pipeline.
     pub fn select_next_job(&mut self) {
         if let Some(details) = &self.details {
             if let Some(stage) = details.stages.get(self.selected_stage) {
                 if self.selected_job < stage.jobs.len().saturating_sub(1) {
                     self.selected_job += 1;
+                } else if self.timeline {
+                    if let Some(next) = (self.selected_stage + 1..details.stages.len())
+                        .find(|&s| !details.stages[s].jobs.is_empty())
+                    {
+                        self.selected_stage = next;
+                        self.selected_job = 0;
+                    }
                 }
             }
         }
@@ -176,9 +351,24 @@ impl PipelineViewState {
     pub fn select_prev_job(&mut self) {
         if self.selected_job > 0 {
             self.selected_job -= 1;
+        } else if self.timeline {
+            if let Some(details) = &self.details {
+                if let Some(prev) = (0..self.selected_stage)
+                    .rev()
+                    .find(|&s| !details.stages[s].jobs.is_empty())
+                {
+                    self.selected_stage = prev;
+                    self.selected_job = details.stages[prev].jobs.len() - 1;
+                }
+            }
         }
     }
 
+    pub fn toggle_timeline(&mut self) {
+        self.timeline = !self.timeline;
+        self.scroll_y = 0;
+    }
+
     pub fn auto_scroll_to_active(&mut self) {
         if let Some(details) = &self.details {
             let mut best: Option<(usize, usize, &str)> = None;
@@ -209,6 +399,13 @@ impl PipelineViewState {
         }
     }
 
+    pub fn selected_job(&self) -> Option<&Job> {
+        self.details
+            .as_ref()
+            .and_then(|d| d.stages.get(self.selected_stage))
+            .and_then(|s| s.jobs.get(self.selected_job))
+    }
+
     pub fn get_selected_job_id(&self) -> Option<u64> {
         self.details
             .as_ref()
@@ -227,13 +424,333 @@ impl PipelineViewState {
     }
 
     pub fn set_job_log(&mut self, job_id: u64, log_text: &str) {
-        self.job_log = parse_gitlab_log(log_text);
+        if self.job_log_job_id != Some(job_id) {
+            self.section_overrides.clear();
+        }
+        let (lines, mut sections) = parse_gitlab_log(log_text);
+        for section in &mut sections {
+            if let Some(collapsed) = self.section_overrides.get(&section.name) {
+                section.collapsed = *collapsed;
+            }
+        }
+        self.job_log = lines;
+        self.job_log_raw = log_text.to_string();
+        self.job_log_sections = sections;
+        self.update_visible_log_lines();
         self.job_log_job_id = Some(job_id);
         self.job_log_loading = false;
         self.job_log_error = None;
         if self.selected_job_is_running() {
             let visible = self.job_log_visible_height as usize;
-            self.job_log_scroll = self.job_log.len().saturating_sub(visible) as u16;
+            self.job_log_scroll = self.job_log_visible.len().saturating_sub(visible) as u16;
+        }
+        if !self.log_search.query.is_empty() {
+            let current = self.log_search.current;
+            self.find_log_matches();
+            self.log_search.current = current.filter(|c| *c < self.log_search.matches.len());
+        }
+    }
+
+    pub fn toggle_job_details(&mut self) {
+        self.job_details_open = !self.job_details_open && self.selected_job().is_some();
+    }
+
+    /// Id of the shown pipeline. `None` for previews.
+    pub fn pipeline_id(&self) -> Option<u64> {
+        self.details
+            .as_ref()
+            .filter(|d| !d.preview)
+            .and_then(|d| d.pipeline.as_ref())
+            .map(|p| p.id)
+    }
+
+    /// Full details of the shown pipeline, falling back to the pipeline the
+    /// jobs were loaded with until they arrive.
+    pub fn info(&self) -> Option<&Pipeline> {
+        let id = self.pipeline_id()?;
+        if self.info_id == Some(id) {
+            if let Some(info) = &self.info {
+                return Some(info);
+            }
+        }
+        self.details.as_ref().and_then(|d| d.pipeline.as_ref())
+    }
+
+    pub fn variables(&self) -> Option<&Result<Vec<PipelineVariable>, String>> {
+        if self.info_id.is_some() && self.info_id == self.pipeline_id() {
+            self.variables.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the id of the shown pipeline if its info is missing or outdated,
+    /// and whether its variables need to be fetched too.
+    pub fn needs_info(&self) -> Option<(u64, bool)> {
+        let id = self.pipeline_id()?;
+        if self.info_pending == Some(id) {
+            return None;
+        }
+        let status = self.details.as_ref()?.pipeline.as_ref()?.status;
+        let stale = match (&self.info, self.info_id == Some(id)) {
+            (Some(info), true) => info.status != status || info.status.is_active(),
+            _ => true,
+        };
+        let with_variables = self.show_variables && self.variables().is_none();
+        (stale || with_variables).then_some((id, with_variables))
+    }
+
+    pub fn set_info_pending(&mut self, pipeline_id: u64) {
+        self.info_pending = Some(pipeline_id);
+    }
+
+    pub fn set_info(
+        &mut self,
+        pipeline_id: u64,
+        info: Result<Pipeline, String>,
+        variables: Option<Result<Vec<PipelineVariable>, String>>,
+    ) {
+        if self.info_pending == Some(pipeline_id) {
+            self.info_pending = None;
+        }
+        if self.pipeline_id() != Some(pipeline_id) {
+            return;
+        }
+        if self.info_id != Some(pipeline_id) {
+            self.info_id = Some(pipeline_id);
+            self.info = None;
+            self.variables = None;
+        }
+        if let Ok(info) = info {
+            self.info = Some(info);
+        }
+        if variables.is_some() {
+            self.variables = variables;
+        }
+    }
+
+    pub fn open_log_search(&mut self) {
+        self.job_log_focused = true;
+        self.log_search.editing = true;
+    }
+
+    pub fn close_log_search(&mut self, keep: bool) {
+        if keep {
+            self.log_search.editing = false;
+        } else {
+            self.log_search = LogSearch {
+                regex: self.log_search.regex,
+                ..LogSearch::default()
+            };
+        }
+    }
+
+    pub fn log_search_input(&mut self, c: char) {
+        self.log_search.query.push(c);
+        self.update_log_search();
+    }
+
+    pub fn log_search_backspace(&mut self) {
+        self.log_search.query.pop();
+        self.update_log_search();
+    }
+
+    pub fn toggle_log_search_regex(&mut self) {
+        self.log_search.regex = !self.log_search.regex;
+        self.update_log_search();
+    }
+
+    fn update_visible_log_lines(&mut self) {
+        self.job_log_visible.clear();
+        let mut hidden_until = 0;
+        for (idx, line) in self.job_log.iter().enumerate() {
+            if idx < hidden_until {
+                continue;
+            }
+            self.job_log_visible.push(idx);
+            if let Some(section) = line.section.map(|s| &self.job_log_sections[s]) {
+                if section.collapsed {
+                    hidden_until = section.end;
+                }
+            }
+        }
+    }
+
+    /// Index of the log line at the top of the job log panel.
+    fn top_log_line(&self) -> usize {
+        self.job_log_visible
+            .get(self.job_log_scroll as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn set_section_collapsed(&mut self, section: usize, collapsed: bool) {
+        let section = &mut self.job_log_sections[section];
+        section.collapsed = collapsed;
+        self.section_overrides
+            .insert(section.name.clone(), collapsed);
+    }
+
+    /// Collapses or expands the section whose header is at the top of the job log panel,
+    /// or else the innermost section containing that line.
+    pub fn toggle_log_section(&mut self) {
+        let top = self.top_log_line();
+        let section = self
+            .job_log
+            .get(top)
+            .and_then(|line| line.section)
+            .or_else(|| {
+                self.job_log_sections
+                    .iter()
+                    .rposition(|s| s.header <= top && top < s.end)
+            });
+        if let Some(section) = section {
+            let collapsed = !self.job_log_sections[section].collapsed;
+            self.set_section_collapsed(section, collapsed);
+            self.update_visible_log_lines();
+            let header = self.job_log_sections[section].header;
+            if let Ok(pos) = self.job_log_visible.binary_search(&header) {
+                self.job_log_scroll = pos as u16;
+            }
+        }
+    }
+
+    /// Collapses all sections, or expands all if none is expanded.
+    pub fn toggle_all_log_sections(&mut self) {
+        let top = self.top_log_line();
+        let collapse = self.job_log_sections.iter().any(|s| !s.collapsed);
+        for section in 0..self.job_log_sections.len() {
+            self.set_section_collapsed(section, collapse);
+        }
+        self.update_visible_log_lines();
+        let pos = self.job_log_visible.partition_point(|line| *line < top);
+        self.job_log_scroll = pos.min(self.job_log_visible.len().saturating_sub(1)) as u16;
+    }
+
+    /// Expands all collapsed sections containing the line.
+    fn reveal_log_line(&mut self, line: usize) {
+        let hidden: Vec<_> = self
+            .job_log_sections
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.collapsed && s.header < line && line < s.end)
+            .map(|(idx, _)| idx)
+            .collect();
+        if !hidden.is_empty() {
+            for section in hidden {
+                self.set_section_collapsed(section, false);
+            }
+            self.update_visible_log_lines();
+        }
+    }
+
+    /// Re-runs the search and selects the first match at or below the top visible line.
+    fn update_log_search(&mut self) {
+        self.find_log_matches();
+        let top = self.top_log_line();
+        let search = &mut self.log_search;
+        search.current = if search.matches.is_empty() {
+            None
+        } else {
+            Some(
+                search
+                    .matches
+                    .iter()
+                    .position(|(line, _)| *line >= top)
+                    .unwrap_or(0),
+            )
+        };
+        self.scroll_to_current_match();
+    }
+
+    fn find_log_matches(&mut self) {
+        let search = &mut self.log_search;
+        search.matches.clear();
+        search.error = None;
+        if search.query.is_empty() {
+            return;
+        }
+
+        let pattern = if search.regex {
+            search.query.clone()
+        } else {
+            regex::escape(&search.query)
+        };
+        // Smart case: case-insensitive unless the query contains upper case letters
+        let case_insensitive = !search.query.chars().any(|c| c.is_uppercase());
+        let re = match RegexBuilder::new(&pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+        {
+            Ok(re) => re,
+            Err(err) => {
+                search.error = Some(err.to_string().lines().last().unwrap_or("").to_string());
+                return;
+            }
+        };
+
+        for (idx, line) in self.job_log.iter().enumerate() {
+            let text = line.plain_text();
+            for m in re.find_iter(&text) {
+                if !m.is_empty() {
+                    search.matches.push((idx, m.range()));
+                }
+            }
+        }
+    }
+
+    pub fn next_log_match(&mut self, forward: bool) {
+        let count = self.log_search.matches.len();
+        if count == 0 {
+            return;
+        }
+        self.log_search.current = Some(match self.log_search.current {
+            Some(c) if forward => (c + 1) % count,
+            Some(c) => (c + count - 1) % count,
+            None => 0,
+        });
+        self.scroll_to_current_match();
+    }
+
+    fn scroll_to_current_match(&mut self) {
+        if let Some(line) = self
+            .log_search
+            .current
+            .and_then(|c| self.log_search.matches.get(c))
+            .map(|(line, _)| *line)
+        {
+            self.scroll_log_to_line(line);
+        }
+    }
+
+    /// Scrolls the job log so that the line is visible, a third from the top if it was not.
+    /// Expands collapsed sections containing the line.
+    fn scroll_log_to_line(&mut self, line: usize) {
+        self.reveal_log_line(line);
+        let pos = match self.job_log_visible.binary_search(&line) {
+            Ok(pos) => pos,
+            Err(_) => return,
+        };
+        let visible = (self.job_log_visible_height as usize).max(1);
+        let top = self.job_log_scroll as usize;
+        if pos < top || pos >= top + visible {
+            self.job_log_scroll = pos.saturating_sub(visible / 3) as u16;
+        }
+    }
+
+    /// Scrolls to the first line that looks like a failure. Returns false if there is none.
+    pub fn jump_to_first_error(&mut self) -> bool {
+        let found = self.job_log.iter().position(|line| {
+            let text = line.plain_text();
+            ERROR_PATTERNS.iter().any(|p| text.contains(p))
+        });
+        match found {
+            Some(line) => {
+                self.job_log_focused = true;
+                self.scroll_log_to_line(line);
+                true
+            }
+            None => false,
         }
     }
 
@@ -264,11 +781,32 @@ impl PipelineViewState {
             .join("\n")
     }
 
+    /// The job log without line numbers, with or without ANSI colors.
+    pub fn job_log_export_text(&self, ansi: bool) -> String {
+        let mut text = self
+            .job_log
+            .iter()
+            .map(|line| {
+                if ansi {
+                    line.content.clone()
+                } else {
+                    line.plain_text()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        text.push('\n');
+        text
+    }
+
     pub fn row_count(&self, available_width: u16) -> u16 {
         let details = match &self.details {
             Some(d) if !d.stages.is_empty() => d,
             _ => return 0,
         };
+        if self.timeline {
+            return details.stages.iter().map(|s| s.jobs.len() as u16).sum();
+        }
         let num_stages = details.stages.len();
         let ideal_widths: Vec<u16> = details.stages.iter().map(calculate_stage_width).collect();
         let connector_total = (num_stages as u16).saturating_sub(1) * CONNECTOR_WIDTH;
@@ -319,9 +857,28 @@ impl PipelineViewState {
         rows
     }
 
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+        self.pinned.clear();
+        self.cache_order.clear();
+        self.clear_previews();
+        self.job_log_cache.clear();
+        self.details = None;
+        self.current_sha = None;
+        self.error = None;
+        self.loading = false;
+        self.clear_job_log();
+    }
+
     pub fn clear_job_log(&mut self) {
+        self.log_search.matches.clear();
+        self.log_search.current = None;
         self.job_log.clear();
+        self.job_log_raw.clear();
+        self.job_log_sections.clear();
+        self.job_log_visible.clear();
         self.job_log_job_id = None;
+        self.job_log_notice = None;
         self.job_log_scroll = 0;
         self.job_log_loading = false;
         self.job_log_error = None;
@@ -344,186 +901,126 @@ fn strip_log_prefix(line: &str) -> (Option<&str>, &str) {
     }
 }
 
-fn extract_section_timestamp(marker: &str) -> Option<u64> {
-    let parts: Vec<&str> = marker.splitn(3, ':').collect();
-    if parts.len() >= 2 {
-        parts[1].parse::<u64>().ok()
-    } else {
-        None
-    }
-}
-
-fn format_duration(seconds: u64) -> String {
+pub(crate) fn format_duration(seconds: u64) -> String {
     let mins = seconds / 60;
     let secs = seconds % 60;
     format!("{:02}:{:02}", mins, secs)
 }
 
-fn parse_gitlab_log(raw: &str) -> Vec<LogLine> {
-    let mut result = Vec::new();
-    let mut section_starts: HashMap<String, u64> = HashMap::new();
-    let mut pending_duration: Option<String> = None;
+/// Parses a raw job trace into lines and the tree of `section_start`/`section_end` blocks.
+/// Section headers become lines of their own that carry the section's duration.
+fn parse_gitlab_log(raw: &str) -> (Vec<LogLine>, Vec<LogSection>) {
+    let mut lines: Vec<LogLine> = Vec::new();
+    let mut sections: Vec<LogSection> = Vec::new();
+    let mut section_starts: Vec<Option<u64>> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
 
     for raw_line in raw.lines() {
         let line = raw_line.trim_end_matches('\r');
-
-        if let Some(start_pos) = line.find("section_end:") {
-            let marker = &line[start_pos + 12..];
-            if let Some(end_ts) = extract_section_timestamp(marker) {
-                let section_name = marker.splitn(3, ':').nth(2).unwrap_or("");
-                if let Some(start_ts) = section_starts.get(section_name) {
-                    let duration = end_ts.saturating_sub(*start_ts);
-                    pending_duration = Some(format_duration(duration));
-                }
+        let (ts, body) = strip_log_prefix(line);
+        let timestamp = ts.map(String::from);
+
+        let mut rest = body;
+        loop {
+            let marker_pos = find_section_marker(rest);
+            let text = &rest[..marker_pos.unwrap_or(rest.len())];
+            if !text.is_empty() {
+                push_log_line(&mut lines, &timestamp, text, open.len(), None);
             }
+            let marker = match marker_pos {
+                Some(pos) => &rest[pos..],
+                None => break,
+            };
 
-            let after_marker = line[start_pos..].find('\n').map(|p| &line[start_pos + p..]);
-            if let Some(rest) = after_marker {
-                if !rest.trim().is_empty() && !rest.contains("section_start:") {
-                    let (ts, _) = strip_log_prefix(line);
-                    let cleaned = rest.replace("\x1b[0K", "");
-                    let content_cleaned = strip_ansi_for_empty_check(&cleaned);
-                    if !content_cleaned.trim().is_empty() {
-                        result.push(LogLine {
-                            timestamp: ts.map(String::from),
-                            styled: parse_ansi_to_styled(&cleaned),
-                            content: cleaned,
-                            duration: None,
-                        });
+            let (is_start, marker) = match marker.strip_prefix("section_start:") {
+                Some(m) => (true, m),
+                None => (false, &marker["section_end:".len()..]),
+            };
+            let marker_end = marker.find('\r').unwrap_or(marker.len());
+            let mut parts = marker[..marker_end].splitn(2, ':');
+            let time = parts.next().and_then(|t| t.parse::<u64>().ok());
+            let name_and_options = parts.next().unwrap_or("");
+            let (name, options) = match name_and_options.find('[') {
+                Some(pos) => (&name_and_options[..pos], &name_and_options[pos..]),
+                None => (name_and_options, ""),
+            };
+            let after = marker[marker_end..]
+                .trim_start_matches('\r')
+                .trim_start_matches("\x1b[0K");
+
+            if is_start {
+                let header_end = find_section_marker(after).unwrap_or(after.len());
+                let header = &after[..header_end];
+                let header_text = if ansi::strip(header).trim().is_empty() {
+                    name
+                } else {
+                    header
+                };
+                sections.push(LogSection {
+                    name: name.to_string(),
+                    header: lines.len(),
+                    end: lines.len() + 1,
+                    collapsed: options.contains("collapsed=true"),
+                });
+                section_starts.push(time);
+                push_log_line(
+                    &mut lines,
+                    &timestamp,
+                    header_text,
+                    open.len(),
+                    Some(sections.len() - 1),
+                );
+                open.push(sections.len() - 1);
+                rest = &after[header_end..];
+            } else {
+                if let Some(pos) = open.iter().rposition(|s| sections[*s].name == name) {
+                    let idx = open[pos];
+                    for open_idx in open.drain(pos..) {
+                        sections[open_idx].end = lines.len();
+                    }
+                    if let (Some(start), Some(end)) = (section_starts[idx], time) {
+                        let header = sections[idx].header;
+                        lines[header].duration = Some(format_duration(end.saturating_sub(start)));
                     }
                 }
+                rest = after;
             }
-            continue;
         }
-
-        if let Some(start_pos) = line.find("section_start:") {
-            let marker = &line[start_pos + 14..];
-            if let Some(start_ts) = extract_section_timestamp(marker) {
-                let section_name = marker.splitn(3, ':').nth(2).unwrap_or("").to_string();
-                let section_name = section_name.trim_end_matches('\r').to_string();
-                section_starts.insert(section_name, start_ts);
-            }
-            continue;
-        }
-
-        let (ts, body) = strip_log_prefix(line);
-        let cleaned = body.replace("\x1b[0K", "").replace("\x1b[0;m", "");
-        let content_cleaned = strip_ansi_for_empty_check(&cleaned);
-        if content_cleaned.trim().is_empty() {
-            continue;
-        }
-
-        let duration = pending_duration.take();
-        if let Some(ref d) = duration {
-            if let Some(last) = result.last_mut() {
-                last.duration = Some(d.clone());
-                continue;
-            }
-        }
-
-        result.push(LogLine {
-            timestamp: ts.map(String::from),
-            styled: parse_ansi_to_styled(&cleaned),
-            content: cleaned,
-            duration,
-        });
     }
 
-    result
-}
-
-fn strip_ansi_for_empty_check(s: &str) -> String {
-    let mut out = String::new();
-    let mut chars = s.chars().peekable();
-    while let Some(ch) = chars.next() {
-        if ch == '\x1b' {
-            while let Some(&c) = chars.peek() {
-                chars.next();
-                if c == 'm' {
-                    break;
-                }
-            }
-        } else {
-            out.push(ch);
-        }
+    for idx in open {
+        sections[idx].end = lines.len();
     }
-    out
+
+    (lines, sections)
 }
 
-fn parse_ansi_to_styled(line: &str) -> Vec<(String, Style)> {
-    let mut segments = Vec::new();
-    let mut current_style = Style::default();
-    let mut current_text = String::new();
-    let mut chars = line.chars().peekable();
-
-    while let Some(ch) = chars.next() {
-        if ch == '\x1b' {
-            if chars.peek() == Some(&'[') {
-                chars.next();
-                let mut code = String::new();
-                while let Some(&c) = chars.peek() {
-                    if c.is_ascii_digit() || c == ';' {
-                        code.push(c);
-                        chars.next();
-                    } else if c == 'm' {
-                        chars.next();
-                        break;
-                    } else {
-                        break;
-                    }
-                }
-                if !current_text.is_empty() {
-                    segments.push((current_text.clone(), current_style));
-                    current_text.clear();
-                }
-                current_style = apply_ansi_code(&code);
-            }
-        } else {
-            current_text.push(ch);
-        }
-    }
-    if !current_text.is_empty() {
-        segments.push((current_text, current_style));
-    }
-    if segments.is_empty() {
-        segments.push((String::new(), Style::default()));
+fn find_section_marker(s: &str) -> Option<usize> {
+    match (s.find("section_start:"), s.find("section_end:")) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
     }
-    segments
 }
 
-fn apply_ansi_code(code: &str) -> Style {
-    let parts: Vec<&str> = code.split(';').collect();
-    match parts.as_slice() {
-        ["0"] | [""] | ["0", ""] => Style::default(),
-        ["1"] => Style::default().add_modifier(Modifier::BOLD),
-        ["31"] => Style::default().fg(Color::Rgb(191, 97, 106)),
-        ["31", "1"] => Style::default()
-            .fg(Color::Rgb(191, 97, 106))
-            .add_modifier(Modifier::BOLD),
-        ["32"] => Style::default().fg(Color::Rgb(163, 190, 140)),
-        ["32", "1"] => Style::default()
-            .fg(Color::Rgb(163, 190, 140))
-            .add_modifier(Modifier::BOLD),
-        ["33"] | ["0", "33"] => Style::default().fg(Color::Rgb(235, 203, 139)),
-        ["33", "1"] => Style::default()
-            .fg(Color::Rgb(235, 203, 139))
-            .add_modifier(Modifier::BOLD),
-        ["34"] => Style::default().fg(Color::Rgb(94, 129, 172)),
-        ["34", "1"] => Style::default()
-            .fg(Color::Rgb(94, 129, 172))
-            .add_modifier(Modifier::BOLD),
-        ["35"] => Style::default().fg(Color::Rgb(180, 142, 173)),
-        ["35", "1"] => Style::default()
-            .fg(Color::Rgb(180, 142, 173))
-            .add_modifier(Modifier::BOLD),
-        ["36"] => Style::default().fg(Color::Rgb(136, 192, 208)),
-        ["36", "1"] => Style::default()
-            .fg(Color::Rgb(136, 192, 208))
-            .add_modifier(Modifier::BOLD),
-        ["37"] | ["37", "1"] => Style::default().fg(Color::Rgb(216, 222, 233)),
-        ["90"] => Style::default().fg(Color::Rgb(76, 86, 106)),
-        _ => Style::default(),
+fn push_log_line(
+    lines: &mut Vec<LogLine>,
+    timestamp: &Option<String>,
+    text: &str,
+    depth: usize,
+    section: Option<usize>,
+) {
+    let cleaned = text.replace("\x1b[0K", "").replace("\x1b[0;m", "");
+    if section.is_none() && ansi::strip(&cleaned).trim().is_empty() {
+        return;
     }
+    lines.push(LogLine {
+        timestamp: timestamp.clone(),
+        styled: ansi::parse(&cleaned),
+        content: cleaned,
+        duration: None,
+        section,
+        depth,
+    });
 }
 
 #[derive(Debug, Clone)]
@@ -575,7 +1072,7 @@ fn status_base_rgb(status: PipelineStatus) -> (u8, u8, u8) {
     }
 }
 
-fn status_color(status: PipelineStatus) -> ratatui::style::Color {
+pub(crate) fn status_color(status: PipelineStatus) -> ratatui::style::Color {
     let (r, g, b) = status_base_rgb(status);
     ratatui::style::Color::Rgb(r, g, b)
 }
@@ -1031,6 +1528,144 @@ fn calculate_layout(details: &PipelineDetails, area: Rect) -> LayoutInfo {
     }
 }
 
+/// Renders one row per job with a bar from its start to its end on a time axis shared by
+/// all jobs, preceded by a lighter segment for the time the job waited for a runner.
+/// Bars of running jobs end at the current time.
+fn render_timeline(
+    details: &PipelineDetails,
+    area: Rect,
+    buf: &mut Buffer,
+    selected: (usize, usize),
+    scroll_y: &mut u16,
+    highlight_style: Style,
+) {
+    const LABEL_WIDTH: u16 = 6;
+
+    let now = time::now();
+    let mut rows = Vec::new();
+    for (stage_idx, stage) in details.stages.iter().enumerate() {
+        for (job_idx, job) in stage.jobs.iter().enumerate() {
+            let timing = job
+                .started_at
+                .as_deref()
+                .and_then(time::parse_timestamp)
+                .and_then(|start| {
+                    let end = match job.finished_at.as_deref().and_then(time::parse_timestamp) {
+                        Some(end) => end,
+                        None if job.status.is_active() => now,
+                        None => start + job.duration?,
+                    };
+                    let queued = start - job.queued_duration.unwrap_or(0.0).max(0.0);
+                    Some((queued, start, end.max(start)))
+                });
+            rows.push(((stage_idx, job_idx), job, timing));
+        }
+    }
+    if rows.is_empty() || area.height < 2 {
+        return;
+    }
+
+    let t0 = rows
+        .iter()
+        .filter_map(|(_, _, t)| t.map(|(queued, _, _)| queued))
+        .fold(f64::INFINITY, f64::min);
+    let t1 = rows
+        .iter()
+        .filter_map(|(_, _, t)| t.map(|(_, _, end)| end))
+        .fold(f64::NEG_INFINITY, f64::max);
+    let span = if t0.is_finite() && t1 > t0 {
+        t1 - t0
+    } else {
+        1.0
+    };
+
+    let max_name = rows
+        .iter()
+        .map(|(_, job, _)| job.name.len())
+        .max()
+        .unwrap_or(0) as u16;
+    let name_width = max_name.clamp(8, 24).min(area.width / 3);
+    let bar_x = area.x + 2 + name_width + 1;
+    let bar_width = area.width.saturating_sub(2 + name_width + 1 + LABEL_WIDTH);
+    let dim = Style::default().fg(theme::TEXT_DIM);
+    let axis_style = Style::default().fg(theme::BORDER);
+
+    if bar_width >= 12 {
+        for x in bar_x..bar_x + bar_width {
+            buf.set_string(x, area.y, "─", axis_style);
+        }
+        buf.set_string(bar_x, area.y, "00:00", dim);
+        let total = if t0.is_finite() {
+            format_duration(span.round() as u64)
+        } else {
+            "--:--".to_string()
+        };
+        buf.set_string(bar_x + bar_width - total.len() as u16, area.y, &total, dim);
+    }
+
+    let rows_area_height = area.height - 1;
+    let selected_row = rows
+        .iter()
+        .position(|(idx, _, _)| *idx == selected)
+        .unwrap_or(0) as u16;
+    if selected_row >= *scroll_y + rows_area_height {
+        *scroll_y = selected_row + 1 - rows_area_height;
+    }
+    if selected_row < *scroll_y {
+        *scroll_y = selected_row;
+    }
+    *scroll_y = (*scroll_y).min((rows.len() as u16).saturating_sub(rows_area_height));
+
+    let col = |t: f64| -> u16 {
+        let offset = ((t - t0) / span * bar_width as f64).floor() as u16;
+        offset.min(bar_width.saturating_sub(1))
+    };
+
+    for (row, (idx, job, timing)) in rows
+        .iter()
+        .enumerate()
+        .skip(*scroll_y as usize)
+        .take(rows_area_height as usize)
+    {
+        let y = area.y + 1 + (row as u16 - *scroll_y);
+        let is_selected = *idx == selected;
+        let mut name_style = Style::default().fg(status_color(job.status));
+        if is_selected {
+            name_style = name_style.patch(highlight_style);
+            buf.set_string(area.x, y, "▸", name_style);
+        }
+        buf.set_string(
+            area.x + 2,
+            y,
+            truncate_str(&job.name, name_width as usize),
+            name_style,
+        );
+        if bar_width == 0 {
+            continue;
+        }
+
+        match timing {
+            Some((queued, start, end)) if t0.is_finite() => {
+                let (queued_col, start_col, end_col) = (col(*queued), col(*start), col(*end));
+                let queued_style = Style::default().fg(status_color_dimmed(job.status));
+                for x in queued_col..start_col {
+                    buf.set_string(bar_x + x, y, "░", queued_style);
+                }
+                let bar_style = Style::default().fg(status_color(job.status));
+                for x in start_col..=end_col {
+                    buf.set_string(bar_x + x, y, "█", bar_style);
+                }
+                let label = format_duration((end - start).round() as u64);
+                buf.set_string(bar_x + bar_width + 1, y, &label, dim);
+            }
+            _ => {
+                let label = truncate_str(&job.status.to_string(), bar_width as usize);
+                buf.set_string(bar_x, y, &label, dim);
+            }
+        }
+    }
+}
+
 fn render_stage(
     buf: &mut Buffer,
     stage: &Stage,
@@ -1150,7 +1785,12 @@ fn render_stage(
         let is_selected = selected == Some(job_idx);
         let max_name = inner_w.saturating_sub(3);
         let job_name = truncate_str(&job.name, max_name);
-        let symbol = job.status.animated_symbol(tick);
+        let retried = job.retried.unwrap_or(false);
+        let symbol = if retried {
+            "↻"
+        } else {
+            job.status.animated_symbol(tick)
+        };
         let job_text = if is_selected {
             format!(" ▸ {}", job_name)
         } else {
@@ -1172,7 +1812,11 @@ fn render_stage(
                 );
             }
         } else {
-            let job_style = Style::default().fg(status_color(job.status));
+            let job_style = if retried {
+                Style::default().fg(status_color_dimmed(job.status))
+            } else {
+                Style::default().fg(status_color(job.status))
+            };
             let line_style = if is_selected {
                 highlight_style.patch(job_style)
             } else {
@@ -1479,13 +2123,30 @@ impl StatefulWidget for PipelineView<'_> {
         };
 
         if details.stages.is_empty() {
-            let msg = "Pipeline has no stages";
+            let msg = if details.preview {
+                "No jobs would run for this commit"
+            } else {
+                "Pipeline has no stages"
+            };
             let x = inner_area.left() + (inner_area.width.saturating_sub(msg.len() as u16)) / 2;
             let y = inner_area.top() + inner_area.height / 2;
             buf.set_string(x, y, msg, Style::default().fg(theme::TEXT_DIM));
             return;
         }
 
+        if state.timeline {
+            let selected = (state.selected_stage, state.selected_job);
+            render_timeline(
+                details,
+                inner_area,
+                buf,
+                selected,
+                &mut state.scroll_y,
+                self.highlight_style,
+            );
+            return;
+        }
+
         let content_area = Rect {
             x: inner_area.x,
             y: inner_area.y,
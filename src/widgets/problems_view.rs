@@ -0,0 +1,52 @@
+use crate::gitlab::problems::Problem;
+use ratatui::widgets::ListState;
+
+pub struct ProblemsViewState {
+    pub job_name: String,
+    /// Commit the job ran on.
+    pub sha: String,
+    pub problems: Vec<Problem>,
+    pub state: ListState,
+}
+
+impl ProblemsViewState {
+    pub fn new(job_name: String, sha: String, problems: Vec<Problem>) -> ProblemsViewState {
+        let mut state = ListState::default();
+        state.select(if problems.is_empty() { None } else { Some(0) });
+        ProblemsViewState {
+            job_name,
+            sha,
+            problems,
+            state,
+        }
+    }
+
+    pub fn fwd(&mut self, steps: usize) {
+        if self.problems.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => std::cmp::min(
+                i.saturating_add(steps),
+                self.problems.len().saturating_sub(1),
+            ),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn bwd(&mut self, steps: usize) {
+        if self.problems.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => i.saturating_sub(steps),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn selected_problem(&self) -> Option<&Problem> {
+        self.state.selected().and_then(|i| self.problems.get(i))
+    }
+}
@@ -0,0 +1,41 @@
+use crate::gitlab_config::RemoteInfo;
+use ratatui::widgets::ListState;
+
+pub struct RemoteListState {
+    pub remotes: Vec<RemoteInfo>,
+    pub state: ListState,
+}
+
+impl RemoteListState {
+    pub fn new(remotes: Vec<RemoteInfo>, current: Option<&str>) -> RemoteListState {
+        let mut state = ListState::default();
+        let selected = current
+            .and_then(|name| remotes.iter().position(|r| r.name.as_deref() == Some(name)))
+            .or(if remotes.is_empty() { None } else { Some(0) });
+        state.select(selected);
+        RemoteListState { remotes, state }
+    }
+
+    pub fn fwd(&mut self, steps: usize) {
+        let i = match self.state.selected() {
+            Some(i) => std::cmp::min(
+                i.saturating_add(steps),
+                self.remotes.len().saturating_sub(1),
+            ),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn bwd(&mut self, steps: usize) {
+        let i = match self.state.selected() {
+            Some(i) => i.saturating_sub(steps),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn selected_remote(&self) -> Option<&RemoteInfo> {
+        self.state.selected().and_then(|i| self.remotes.get(i))
+    }
+}
@@ -0,0 +1,174 @@
+//! ANSI parsing of recorded CI job traces (`tests/fixtures/ansi`).
+
+use git_igitt::theme;
+use git_igitt::util::ansi;
+use git_igitt::widgets::pipeline_view::PipelineViewState;
+use ratatui::style::{Color, Modifier, Style};
+
+const GITLAB_RUNNER: &str = include_str!("fixtures/ansi/gitlab_runner.log");
+const CARGO: &str = include_str!("fixtures/ansi/cargo.log");
+const NODE: &str = include_str!("fixtures/ansi/node.log");
+const DOCKER: &str = include_str!("fixtures/ansi/docker.log");
+
+const CORPUS: &[&str] = &[GITLAB_RUNNER, CARGO, NODE, DOCKER];
+
+fn line(trace: &str, index: usize) -> Vec<(String, Style)> {
+    ansi::parse(trace.lines().nth(index).expect("fixture line"))
+}
+
+fn style_of(segments: &[(String, Style)], text: &str) -> Style {
+    segments
+        .iter()
+        .find(|(t, _)| t.contains(text))
+        .map(|(_, style)| *style)
+        .unwrap_or_else(|| panic!("no segment containing {:?} in {:?}", text, segments))
+}
+
+#[test]
+fn no_escape_sequences_remain() {
+    for trace in CORPUS {
+        for raw in trace.lines() {
+            let plain = ansi::strip(raw);
+            assert!(
+                !plain.chars().any(|c| c.is_control() && c != '\t'),
+                "control characters left in {:?}",
+                plain
+            );
+        }
+    }
+}
+
+#[test]
+fn gitlab_runner_colors() {
+    let segments = line(GITLAB_RUNNER, 2);
+    assert_eq!(
+        style_of(&segments, "Preparing"),
+        Style::default()
+            .fg(theme::frost::NORD8)
+            .add_modifier(Modifier::BOLD)
+    );
+    assert_eq!(
+        ansi::strip(GITLAB_RUNNER.lines().next().unwrap()),
+        "Running with gitlab-runner 17.4.0 (b92ee590)"
+    );
+
+    let error = line(GITLAB_RUNNER, 17);
+    assert_eq!(
+        style_of(&error, "ERROR"),
+        Style::default()
+            .fg(theme::aurora::NORD11)
+            .add_modifier(Modifier::BOLD)
+    );
+}
+
+#[test]
+fn cargo_attributes_accumulate() {
+    let compiling = line(CARGO, 1);
+    assert_eq!(
+        style_of(&compiling, "Compiling"),
+        Style::default()
+            .fg(theme::aurora::NORD14)
+            .add_modifier(Modifier::BOLD)
+    );
+    assert_eq!(style_of(&compiling, "git-igitt"), Style::default());
+
+    let arrow = line(CARGO, 3);
+    assert_eq!(
+        style_of(&arrow, "-->"),
+        Style::default()
+            .fg(theme::frost::NORD9)
+            .add_modifier(Modifier::BOLD)
+    );
+
+    let failed = line(CARGO, 6);
+    assert_eq!(
+        style_of(&failed, "FAILED"),
+        Style::default().fg(theme::aurora::NORD11)
+    );
+}
+
+#[test]
+fn extended_colors_and_attributes() {
+    let npm = line(NODE, 0);
+    assert_eq!(
+        ansi::strip(NODE.lines().next().unwrap()),
+        "npm WARN deprecated inflight@1.0.6"
+    );
+    assert_eq!(
+        style_of(&npm, "npm"),
+        Style::default().fg(Color::Rgb(255, 135, 0))
+    );
+    assert_eq!(
+        style_of(&npm, "WARN"),
+        Style::default()
+            .fg(Color::Indexed(214))
+            .bg(Color::Indexed(236))
+    );
+
+    let done = line(NODE, 1);
+    assert_eq!(
+        style_of(&done, "Done"),
+        Style::default().add_modifier(Modifier::DIM | Modifier::ITALIC)
+    );
+
+    let pass = line(NODE, 2);
+    assert_eq!(
+        style_of(&pass, "PASS"),
+        Style::default()
+            .fg(theme::aurora::NORD14)
+            .add_modifier(Modifier::REVERSED | Modifier::BOLD)
+    );
+    assert_eq!(style_of(&pass, "src/app.test.ts"), Style::default());
+
+    let renders = line(NODE, 3);
+    assert_eq!(
+        style_of(&renders, "renders"),
+        Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+    );
+    assert_eq!(style_of(&renders, "(5 ms)"), Style::default());
+
+    let links = line(NODE, 4);
+    assert_eq!(
+        ansi::strip(NODE.lines().nth(4).unwrap()),
+        "docs and colon form"
+    );
+    assert_eq!(
+        style_of(&links, "colon form"),
+        Style::default().fg(Color::Rgb(94, 129, 172))
+    );
+}
+
+#[test]
+fn carriage_return_and_erase_overwrite() {
+    let lines: Vec<String> = DOCKER.lines().map(ansi::strip).collect();
+    assert_eq!(lines[0], "#5 [2/4] RUN apt-get update 1.2s");
+    assert_eq!(lines[1], "Downloading 100%");
+    assert_eq!(lines[2], "=> [internal] load build definition");
+    assert_eq!(lines[3], "plain after charset");
+}
+
+#[test]
+fn job_log_uses_parser() {
+    let mut state = PipelineViewState::default();
+    state.set_job_log(1, GITLAB_RUNNER);
+
+    let names: Vec<_> = state
+        .job_log_sections
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "prepare_executor",
+            "get_sources",
+            "step_script",
+            "cleanup_file_variables"
+        ]
+    );
+    for line in &state.job_log {
+        assert!(!line.plain_text().contains('\x1b'));
+    }
+    let header = &state.job_log[state.job_log_sections[0].header];
+    assert_eq!(header.plain_text(), "Preparing the \"docker\" executor");
+}